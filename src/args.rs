@@ -1,6 +1,9 @@
 use clap::{Parser, Subcommand};
 
+#[cfg(feature = "github")]
+pub mod auth;
 pub mod bisect;
+pub mod pull_requests;
 
 /// The tool's CLI arguments.
 #[derive(Parser, Eq, PartialEq, Debug)]
@@ -31,6 +34,13 @@ pub(crate) struct CommonOptions {
 
 #[derive(Subcommand, Eq, PartialEq, Debug)]
 pub(crate) enum Command {
+    #[cfg(feature = "github")]
+    #[command(subcommand)]
+    Auth(auth::Command),
+
     #[command(subcommand)]
     Bisect(bisect::Command),
+
+    #[command(subcommand)]
+    PullRequests(pull_requests::Command),
 }
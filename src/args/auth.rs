@@ -0,0 +1,42 @@
+use std::process::ExitStatus;
+
+use clap::{Parser, Subcommand};
+use git_assist::{command::auth::login, host::DEFAULT_OAUTH_SCOPE};
+use url::Url;
+
+use super::CommonOptions;
+
+#[derive(Subcommand, Eq, PartialEq, Debug)]
+pub(crate) enum Command {
+    /// A sub-command for logging in to Github via the browser (i.e. the OAuth device flow),
+    /// storing the access token for later commands.
+    Login(LoginCommand),
+}
+
+#[derive(Parser, Eq, PartialEq, Debug)]
+pub(crate) struct LoginCommand {
+    /// The client ID of the OAuth (or Github) App to authorize, which needs to have the device flow enabled.
+    #[arg(long, value_name = "ID")]
+    pub(crate) client_id: String,
+
+    /// The url of the Github (Enterprise Server) instance to log in to.
+    #[arg(long, value_name = "URL", default_value = "https://github.com/")]
+    pub(crate) host: String,
+
+    /// The scopes to request, separated by spaces (e.g. `repo` for access to private repositories).
+    #[arg(long, value_name = "SCOPES", default_value = DEFAULT_OAUTH_SCOPE)]
+    pub(crate) scope: String,
+
+    /// Common options.
+    #[command(flatten)]
+    pub(crate) common: CommonOptions,
+}
+
+impl LoginCommand {
+    pub async fn run(&self) -> anyhow::Result<ExitStatus> {
+        let host_url = Url::parse(&self.host)
+            .map_err(|err| anyhow::anyhow!("Invalid host url {host:?}: {err}", host = self.host))?;
+
+        login(&host_url, &self.client_id, &self.scope).await
+    }
+}
@@ -1,21 +1,48 @@
-use std::process::ExitStatus;
+use std::{path::PathBuf, process::ExitStatus, str::FromStr};
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use git_assist::{
-    command::bisect::{skip_pull_requests, SkipPullRequestsConfigBuilder},
-    host::{GitHost, GithubApi, SupportedHost},
+    command::bisect::{
+        bisect_by_pull_request, bisect_run, skip_pull_requests, start_bisect, BisectInProgress,
+        SkipPullRequestsConfig, SkipPullRequestsConfigBuilder,
+    },
+    host::{
+        AuthMethod, AuthOptions, CachingHost, FileHost, GitHost, MergeMessagesHost,
+        PullRequestFilter, PullRequestFilters, PullRequestRefsHost, SupportedHost,
+    },
 };
 
+#[cfg(feature = "azure")]
+use git_assist::host::AzureDevOpsApi;
+#[cfg(feature = "bitbucket")]
+use git_assist::host::BitbucketApi;
+#[cfg(feature = "gitea")]
+use git_assist::host::GiteaApi;
+#[cfg(feature = "gitlab")]
+use git_assist::host::GitlabApi;
+#[cfg(feature = "github")]
+use git_assist::host::{GithubApi, GithubGraphqlApi};
+
 use super::CommonOptions;
 
 #[derive(Subcommand, Eq, PartialEq, Debug)]
 pub(crate) enum Command {
     /// A sub-command for skipping all internal pull request commits (i.e. `base..head^`).
     SkipPullRequests(SkipPullRequestsCommand),
+
+    /// A sub-command for starting a bisect with all internal pull request commits skipped.
+    Start(StartCommand),
+
+    /// A sub-command for bisecting pull requests first, then the commits of the first bad one.
+    ByPullRequest(ByPullRequestCommand),
+
+    /// A sub-command for bisecting automatically by running a command, with all internal pull request commits skipped.
+    Run(RunCommand),
 }
 
-#[derive(Parser, Eq, PartialEq, Debug)]
-pub(crate) struct SkipPullRequestsCommand {
+/// Options shared by all sub-commands that skip internal pull request commits.
+#[derive(Args, Eq, PartialEq, Debug)]
+pub(crate) struct SkipPullRequestsOptions {
     /// Remote url to fetch pull requests from.
     #[arg(long)]
     pub(crate) remote_url: Option<String>,
@@ -24,17 +51,255 @@ pub(crate) struct SkipPullRequestsCommand {
     #[arg(long, hide = true)]
     pub(crate) directory: Option<String>,
 
-    /// A known "good" commit.
+    /// A known "good" commit (can be passed multiple times).
+    ///
+    /// Defaults to the good commits of the `git bisect` session in progress, if any.
     #[arg(long)]
-    pub(crate) good: Option<String>,
+    pub(crate) good: Vec<String>,
 
     /// A known "bad" commit.
+    ///
+    /// Defaults to the bad commit of the `git bisect` session in progress, if any.
     #[arg(long)]
     pub(crate) bad: Option<String>,
 
+    /// The branch to restrict pull requests to, by their base branch.
+    ///
+    /// Defaults to the branch that contains the "bad" commit.
+    #[arg(long)]
+    pub(crate) base_branch: Option<String>,
+
+    /// Only skip pull requests matching the filter (can be passed multiple times).
+    ///
+    /// Filters are of the form `label:<name>`, `author:<login>`, `title:<regex>`, or `number:<number>`.
+    #[arg(long)]
+    pub(crate) include: Vec<String>,
+
+    /// Never skip pull requests matching the filter (can be passed multiple times).
+    ///
+    /// Filters are of the form `label:<name>`, `author:<login>`, `title:<regex>`, or `number:<number>`.
+    #[arg(long)]
+    pub(crate) exclude: Vec<String>,
+
+    /// The type of the remote's host: `github`, `gitlab`, `gitea`, `bitbucket`, or `azure`.
+    ///
+    /// Defaults to `git config assist.host.<hostname>.type`, if set, otherwise gets detected.
+    #[arg(long, value_name = "TYPE")]
+    pub(crate) host_type: Option<SupportedHost>,
+
+    /// The url of the host's API (e.g. `https://github.example.com/api/v3/`).
+    ///
+    /// Defaults to `git config assist.host.<hostname>.apiUrl`, if set, otherwise gets derived from the remote url.
+    #[arg(long, value_name = "URL")]
+    pub(crate) api_url: Option<String>,
+
+    /// Request pull requests via the host's GraphQL API, instead of its REST API (Github only).
+    #[arg(long)]
+    pub(crate) graphql: bool,
+
+    /// Cache pull requests within `.git/assist/pull-requests/`, so that later runs only request those merged since.
+    #[arg(long)]
+    pub(crate) cache: bool,
+
+    /// Discard any cached pull requests, requesting them all over again (implies `--cache`).
+    #[arg(long, conflicts_with = "offline")]
+    pub(crate) refresh: bool,
+
+    /// Only use cached pull requests, without requesting any from the host.
+    #[arg(long)]
+    pub(crate) offline: bool,
+
+    /// Derive merged pull requests from fetched `refs/pull/*/head` refs, instead of requesting them from the host.
+    ///
+    /// Only pull requests merged via merge commits are found, as squashed or rebased ones don't keep their heads.
+    #[arg(long, conflicts_with_all = ["graphql", "cache", "refresh", "offline"])]
+    pub(crate) pull_request_refs: bool,
+
+    /// Reconstruct merged pull requests from the messages of merge commits, instead of requesting them from the host.
+    ///
+    /// Recognizes the merge (and squash) commit messages of Github, Gitlab, Bitbucket and Azure DevOps by default.
+    #[arg(long, conflicts_with_all = ["graphql", "cache", "refresh", "offline", "pull_request_refs"])]
+    pub(crate) merge_messages: bool,
+
+    /// A regular expression recognizing merge commit messages (can be passed multiple times).
+    ///
+    /// Patterns capture the pull request's number via a `(?<number>…)` group, and optionally its title via a `(?<title>…)` group.
+    #[arg(long, value_name = "REGEX", requires = "merge_messages")]
+    pub(crate) merge_message_pattern: Vec<String>,
+
+    /// A JSON file to load pull requests from, instead of requesting them from the host.
+    ///
+    /// Such files are written by `git assist pull-requests export`.
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["graphql", "cache", "refresh", "offline", "pull_request_refs", "merge_messages"]
+    )]
+    pub(crate) pull_requests_file: Option<PathBuf>,
+
+    /// The method to authenticate with the host: `none`, `token`, or `app` (Github only).
+    ///
+    /// Defaults to a token, if one is found, otherwise gets prompted for.
+    #[arg(long, value_name = "METHOD")]
+    pub(crate) auth: Option<AuthMethod>,
+
+    /// A file to read the host's access token from.
+    ///
+    /// Takes precedence over the `GH_TOKEN` and `GITHUB_TOKEN` (Github), `GITLAB_TOKEN` (Gitlab), etc. environment variables.
+    #[arg(long, value_name = "PATH")]
+    pub(crate) token_file: Option<PathBuf>,
+
+    /// Fail instead of prompting for credentials, commits or the remote url (implied when stdin is not a terminal).
+    #[arg(long)]
+    pub(crate) non_interactive: bool,
+
+    /// The ID of the Github App to authenticate as (implies `--auth app`).
+    #[arg(long, value_name = "ID")]
+    pub(crate) app_id: Option<u64>,
+
+    /// The PEM file containing the Github App's private key (implies `--auth app`).
+    #[arg(long, value_name = "PATH")]
+    pub(crate) app_key_file: Option<PathBuf>,
+
+    /// The ID of the Github App's installation to authenticate as (implies `--auth app`).
+    ///
+    /// Defaults to the app's installation that has access to the repository.
+    #[arg(long, value_name = "ID")]
+    pub(crate) installation_id: Option<u64>,
+
     /// Perform a "dry" run.
     #[arg(long)]
     pub(crate) dry_run: bool,
+}
+
+impl SkipPullRequestsOptions {
+    pub(crate) fn config(&self) -> anyhow::Result<SkipPullRequestsConfig> {
+        self.config_builder()?.build()
+    }
+
+    pub(crate) fn config_builder(&self) -> anyhow::Result<SkipPullRequestsConfigBuilder> {
+        let filters = PullRequestFilters {
+            include: parse_filters(&self.include)?,
+            exclude: parse_filters(&self.exclude)?,
+        };
+
+        let auth = AuthOptions {
+            method: self.auth,
+            token_file: self.token_file.clone(),
+            non_interactive: self.non_interactive,
+            app_id: self.app_id,
+            app_key_file: self.app_key_file.clone(),
+            installation_id: self.installation_id,
+        };
+
+        let builder = SkipPullRequestsConfigBuilder::new()
+            .remote_url(self.remote_url.clone())
+            .directory(self.directory.clone())
+            .good(self.good.clone())
+            .bad(self.bad.clone())
+            .base_branch(self.base_branch.clone())
+            .filters(filters)
+            .host_type(self.host_type)
+            .api_url(self.api_url.clone())
+            .graphql(self.graphql)
+            .cache(self.cache)
+            .refresh(self.refresh)
+            .offline(self.offline)
+            .pull_request_refs(self.pull_request_refs)
+            .merge_messages(self.merge_messages)
+            .merge_message_patterns(self.merge_message_pattern.clone())
+            .pull_requests_file(self.pull_requests_file.clone())
+            .auth(auth)
+            .dry_run(self.dry_run);
+
+        Ok(builder)
+    }
+
+    pub(crate) async fn host(
+        &self,
+        config: &SkipPullRequestsConfig,
+    ) -> anyhow::Result<Box<dyn GitHost>> {
+        let repository = &config.repository;
+        let api_url = config.api_url.clone();
+        let cache_directory = config.cache_directory.clone();
+
+        // Pull requests loaded from a file, or derived from local refs or commits neither need a host, nor a cache:
+        if let Some(path) = &config.pull_requests_file {
+            return Ok(Box::new(FileHost::open(path)?));
+        }
+
+        if config.pull_request_refs {
+            return Ok(Box::new(PullRequestRefsHost::new(config.directory.clone())));
+        }
+
+        if config.merge_messages {
+            return Ok(Box::new(MergeMessagesHost::new(
+                config.directory.clone(),
+                &config.merge_message_patterns,
+            )?));
+        }
+
+        // Neither detecting, nor authenticating with the host is necessary when offline:
+        if config.offline {
+            return Ok(Box::new(CachingHost::offline(cache_directory)));
+        }
+
+        let supported_host = match config.host_type {
+            Some(host_type) => host_type,
+            None => SupportedHost::detect(&repository.parsed_url).await?,
+        };
+
+        if config.graphql && supported_host != SupportedHost::Github {
+            anyhow::bail!(
+                "Requesting pull requests via GraphQL is only supported for Github hosts"
+            );
+        }
+
+        let host: Box<dyn GitHost + Send + Sync> = match supported_host {
+            #[cfg(feature = "github")]
+            SupportedHost::Github if config.graphql => {
+                Box::new(GithubGraphqlApi::authenticated(repository, api_url, &config.auth).await?)
+            }
+            #[cfg(feature = "github")]
+            SupportedHost::Github => Box::new(GithubApi::authenticated(repository, api_url, &config.auth).await?),
+            #[cfg(feature = "gitlab")]
+            SupportedHost::Gitlab => Box::new(GitlabApi::authenticated(repository, api_url, &config.auth)?),
+            #[cfg(feature = "gitea")]
+            SupportedHost::Gitea => Box::new(GiteaApi::authenticated(repository, api_url, &config.auth)?),
+            #[cfg(feature = "bitbucket")]
+            SupportedHost::Bitbucket => Box::new(BitbucketApi::authenticated(repository, api_url, &config.auth)?),
+            #[cfg(feature = "azure")]
+            SupportedHost::AzureDevOps => {
+                Box::new(AzureDevOpsApi::authenticated(repository, api_url, &config.auth)?)
+            }
+            #[allow(unreachable_patterns)]
+            host_type => anyhow::bail!(
+                "Support for {host_type} hosts is only available when compiled with `--features \"{host_type}\"`"
+            ),
+        };
+
+        if !config.cache && !config.refresh {
+            return Ok(host);
+        }
+
+        Ok(Box::new(
+            CachingHost::new(host, cache_directory).refresh(config.refresh),
+        ))
+    }
+}
+
+fn parse_filters(filters: &[String]) -> anyhow::Result<Vec<PullRequestFilter>> {
+    filters
+        .iter()
+        .map(|filter| PullRequestFilter::from_str(filter))
+        .collect()
+}
+
+#[derive(Parser, Eq, PartialEq, Debug)]
+pub(crate) struct SkipPullRequestsCommand {
+    /// Pull request options.
+    #[command(flatten)]
+    pub(crate) options: SkipPullRequestsOptions,
 
     /// Common options.
     #[command(flatten)]
@@ -43,18 +308,100 @@ pub(crate) struct SkipPullRequestsCommand {
 
 impl SkipPullRequestsCommand {
     pub async fn run(&self) -> anyhow::Result<ExitStatus> {
-        let config = SkipPullRequestsConfigBuilder::new()
-            .remote_url(self.remote_url.clone())
-            .directory(self.directory.clone())
-            .good(self.good.clone())
-            .bad(self.bad.clone())
-            .dry_run(self.dry_run)
+        let config = self.options.config()?;
+        let host = self.options.host(&config).await?;
+
+        skip_pull_requests(&*host, &config).await
+    }
+}
+
+#[derive(Parser, Eq, PartialEq, Debug)]
+pub(crate) struct StartCommand {
+    /// Pull request options.
+    #[command(flatten)]
+    pub(crate) options: SkipPullRequestsOptions,
+
+    /// Reset any bisect already in progress, instead of refusing to start.
+    #[arg(long)]
+    pub(crate) reset: bool,
+
+    /// Common options.
+    #[command(flatten)]
+    pub(crate) common: CommonOptions,
+}
+
+impl StartCommand {
+    pub async fn run(&self) -> anyhow::Result<ExitStatus> {
+        let config = self
+            .options
+            .config_builder()?
+            .bisect_in_progress(bisect_in_progress(self.reset))
             .build()?;
+        let host = self.options.host(&config).await?;
 
-        let host: Box<dyn GitHost> = match SupportedHost::try_from(&config.repository.parsed_url)? {
-            SupportedHost::Github => Box::new(GithubApi::authenticated()?),
-        };
+        start_bisect(&*host, &config, self.reset).await
+    }
+}
 
-        skip_pull_requests(&*host, &config).await
+/// Returns how commands starting a new bisect treat one that's already in progress.
+fn bisect_in_progress(reset: bool) -> BisectInProgress {
+    if reset {
+        BisectInProgress::Reset
+    } else {
+        BisectInProgress::Refuse
+    }
+}
+
+#[derive(Parser, Eq, PartialEq, Debug)]
+pub(crate) struct ByPullRequestCommand {
+    /// Pull request options.
+    #[command(flatten)]
+    pub(crate) options: SkipPullRequestsOptions,
+
+    /// Reset any bisect already in progress, instead of refusing to start.
+    #[arg(long)]
+    pub(crate) reset: bool,
+
+    /// Common options.
+    #[command(flatten)]
+    pub(crate) common: CommonOptions,
+}
+
+impl ByPullRequestCommand {
+    pub async fn run(&self) -> anyhow::Result<ExitStatus> {
+        let config = self
+            .options
+            .config_builder()?
+            .bisect_in_progress(bisect_in_progress(self.reset))
+            .build()?;
+        let host = self.options.host(&config).await?;
+
+        bisect_by_pull_request(&*host, &config, self.reset).await
+    }
+}
+
+#[derive(Parser, Eq, PartialEq, Debug)]
+pub(crate) struct RunCommand {
+    /// Pull request options.
+    #[command(flatten)]
+    pub(crate) options: SkipPullRequestsOptions,
+
+    /// The command to run on each commit under test.
+    ///
+    /// Exit code `0` marks a commit as good, `125` as skipped, and `1..=127` as bad.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+    pub(crate) command: Vec<String>,
+
+    /// Common options.
+    #[command(flatten)]
+    pub(crate) common: CommonOptions,
+}
+
+impl RunCommand {
+    pub async fn run(&self) -> anyhow::Result<ExitStatus> {
+        let config = self.options.config()?;
+        let host = self.options.host(&config).await?;
+
+        bisect_run(&*host, &config, &self.command).await
     }
 }
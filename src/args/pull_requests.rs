@@ -0,0 +1,36 @@
+use std::{path::PathBuf, process::ExitStatus};
+
+use clap::{Parser, Subcommand};
+use git_assist::command::pull_requests::export_pull_requests;
+
+use super::{bisect::SkipPullRequestsOptions, CommonOptions};
+
+#[derive(Subcommand, Eq, PartialEq, Debug)]
+pub(crate) enum Command {
+    /// A sub-command for exporting the pull requests of a bisect's range to a file (e.g. for `--pull-requests-file`).
+    Export(ExportCommand),
+}
+
+#[derive(Parser, Eq, PartialEq, Debug)]
+pub(crate) struct ExportCommand {
+    /// Pull request options.
+    #[command(flatten)]
+    pub(crate) options: SkipPullRequestsOptions,
+
+    /// The JSON file to write the pull requests to (or `-` for stdout).
+    #[arg(long, value_name = "PATH")]
+    pub(crate) output: PathBuf,
+
+    /// Common options.
+    #[command(flatten)]
+    pub(crate) common: CommonOptions,
+}
+
+impl ExportCommand {
+    pub async fn run(&self) -> anyhow::Result<ExitStatus> {
+        let config = self.options.config()?;
+        let host = self.options.host(&config).await?;
+
+        export_pull_requests(&*host, &config, &self.output).await
+    }
+}
@@ -1 +1,4 @@
+#[cfg(feature = "github")]
+pub mod auth;
 pub mod bisect;
+pub mod pull_requests;
@@ -0,0 +1,19 @@
+use std::{os::unix::process::ExitStatusExt, process::ExitStatus};
+
+use url::Url;
+
+use crate::host::GithubApi;
+
+/// Logs in to the Github (Enterprise Server) instance at `host_url` via the OAuth device flow
+/// of the OAuth (or Github) App with the given client ID, storing the access token
+/// for authenticating with the host's repositories later on.
+pub async fn login(host_url: &Url, client_id: &str, scope: &str) -> anyhow::Result<ExitStatus> {
+    let path = GithubApi::login(host_url, client_id, scope).await?;
+
+    eprintln!(
+        "Logged in to {host_url}, stored token in {path}",
+        path = path.display()
+    );
+
+    Ok(ExitStatus::from_raw(0))
+}
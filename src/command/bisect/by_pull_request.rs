@@ -0,0 +1,172 @@
+use std::{collections::HashMap, os::unix::process::ExitStatusExt, process::ExitStatus};
+
+use git2::{Commit as GitCommit, Oid, Repository as GitRepository};
+use inquire::Select;
+
+use crate::{
+    git::{mark_commits, BisectMark},
+    host::GitHost,
+};
+
+use super::{
+    first_parents_in_range, print_pull_request, pull_requests_in_range, resolve_range,
+    search::{bisect_linearly, BisectOutcome, Verdict},
+    start::begin_bisect,
+    ClassifiedPullRequest, MergeStrategy, SkipPullRequestsConfig,
+};
+
+/// Bisects at the granularity of pull requests, then drills down into the culprit's commits.
+///
+/// The first phase only bisects the first-parent merge commits of the pull requests
+/// that got merged within the `good..bad` range, just as if they had all been squashed.
+///
+/// Once the first bad pull request has been found, the second phase bisects
+/// the commits between the last good pull request and it, followed by the culprit's
+/// internal commits (for merge commits, as a chain of their own), just as if it had been rebased.
+///
+/// Fails if a bisect is already in progress, unless `reset` is `true`,
+/// in which case the bisect in progress gets reset first.
+pub async fn bisect_by_pull_request(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    reset: bool,
+) -> anyhow::Result<ExitStatus> {
+    eprintln!("Opening git repository ...");
+    let repository = GitRepository::open(&config.directory)?;
+
+    let (good, bad) = resolve_range(&repository, config)?;
+
+    let pull_requests: HashMap<Oid, ClassifiedPullRequest> =
+        pull_requests_in_range(host, config, &repository, (&good, bad))
+            .await?
+            .into_iter()
+            .map(|classified| (classified.merge_commit, classified))
+            .collect();
+
+    let candidates: Vec<Oid> = first_parents_in_range(&repository, (&good, bad))?
+        .into_iter()
+        .filter(|commit_id| pull_requests.contains_key(commit_id))
+        .collect();
+
+    if config.dry_run {
+        for commit_id in candidates {
+            print_pull_request(&pull_requests[&commit_id]);
+            println!("{commit_id}");
+        }
+
+        return Ok(ExitStatus::from_raw(0));
+    }
+
+    begin_bisect(&repository, &config.directory, reset, false)?;
+
+    mark_commits(&repository, BisectMark::Good, good.iter().copied())?;
+    mark_commits(&repository, BisectMark::Bad, [bad])?;
+
+    eprintln!(
+        "Bisecting {count} pull requests ...",
+        count = candidates.len()
+    );
+
+    let outcome = bisect_linearly(&repository, candidates, bad, |commit| {
+        if let Some(classified) = pull_requests.get(&commit.id()) {
+            print_pull_request(classified);
+        }
+        prompt_verdict(commit)
+    })?;
+
+    let (first_bad, last_good) = match outcome {
+        BisectOutcome::Found {
+            first_bad,
+            last_good,
+        } => (first_bad, last_good),
+        BisectOutcome::Ambiguous { candidates } => {
+            print_ambiguous(&candidates);
+            return Ok(ExitStatus::from_raw(0));
+        }
+    };
+
+    let culprit = pull_requests.get(&first_bad);
+
+    match culprit {
+        Some(classified) => {
+            println!("First bad pull request:");
+            print_pull_request(classified);
+        }
+        None => {
+            println!("First bad pull request: none, {first_bad} was not merged via a pull request");
+        }
+    }
+
+    let last_good: Vec<Oid> = match last_good {
+        Some(last_good) => vec![last_good],
+        None => good,
+    };
+
+    // Any commits pushed directly to the base branch since the last good pull request come first:
+    let candidates = first_parents_in_range(&repository, (&last_good, first_bad))?;
+
+    eprintln!(
+        "Bisecting {count} commits preceding first bad pull request ...",
+        count = candidates.len()
+    );
+
+    let mut outcome = bisect_linearly(&repository, candidates, first_bad, prompt_verdict)?;
+
+    // The internal commits of merge commits are not part of the first-parent history,
+    // but form a chain of their own, from the merge's base (i.e. `MERGE^1`) to its branch (i.e. `MERGE^2`):
+    if let (
+        BisectOutcome::Found {
+            first_bad: found, ..
+        },
+        Some(ClassifiedPullRequest {
+            strategy: MergeStrategy::MergeCommit,
+            skip_range: Some(skip_range),
+            ..
+        }),
+    ) = (&outcome, culprit)
+    {
+        if *found == first_bad {
+            let mut candidates =
+                first_parents_in_range(&repository, (&[skip_range.start], skip_range.end))?;
+            candidates.push(skip_range.end);
+
+            eprintln!(
+                "Bisecting {count} commits of first bad pull request ...",
+                count = candidates.len()
+            );
+
+            outcome = bisect_linearly(&repository, candidates, first_bad, prompt_verdict)?;
+        }
+    }
+
+    match outcome {
+        BisectOutcome::Found { first_bad, .. } => {
+            let commit = repository.find_commit(first_bad)?;
+
+            println!(
+                "{first_bad} is the first bad commit: {summary:?}",
+                summary = commit.summary().unwrap_or_default()
+            );
+        }
+        BisectOutcome::Ambiguous { candidates } => {
+            print_ambiguous(&candidates);
+        }
+    }
+
+    Ok(ExitStatus::from_raw(0))
+}
+
+fn prompt_verdict(_commit: &GitCommit<'_>) -> anyhow::Result<Verdict> {
+    let choices = vec![Verdict::Good, Verdict::Bad, Verdict::Skip];
+
+    Ok(Select::new("Is this commit good or bad?", choices).prompt()?)
+}
+
+fn print_ambiguous(candidates: &[Oid]) {
+    println!("There are only skipped commits left to test.");
+    println!("The first bad commit could be any of:");
+
+    for candidate in candidates {
+        println!("{candidate}");
+    }
+}
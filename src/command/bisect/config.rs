@@ -1,9 +1,13 @@
 use std::{path::PathBuf, str::FromStr};
 
-use git2::{Remote as GitRemote, Repository as GitRepository};
+use git2::{BranchType as GitBranchType, Remote as GitRemote, Repository as GitRepository};
 use inquire::{Select, Text};
+use url::Url;
 
-use crate::host::GitRepositoryUrl;
+use crate::{
+    git::{bisect_marks, config_string, is_bisecting, run_git_bisect},
+    host::{AuthOptions, GitRepositoryUrl, PullRequestFilters, SupportedHost},
+};
 
 use super::SkipPullRequestsConfig;
 
@@ -27,12 +31,40 @@ impl std::fmt::Display for RepositoryUrlChoice<'_> {
     }
 }
 
+/// How to treat a bisect that's already in progress when building a `SkipPullRequestsConfig`.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub enum BisectInProgress {
+    /// Continue the bisect, defaulting to its good/bad commits.
+    #[default]
+    Continue,
+
+    /// Refuse to build the config, before prompting for anything.
+    Refuse,
+
+    /// Reset the bisect, without using any of its good/bad commits.
+    Reset,
+}
+
 /// Builder for creating `SkipPullRequestsConfig` from command-line arguments and user input.
 pub struct SkipPullRequestsConfigBuilder {
     pub remote_url: Option<String>,
     pub directory: Option<String>,
-    pub good: Option<String>,
+    pub good: Vec<String>,
     pub bad: Option<String>,
+    pub base_branch: Option<String>,
+    pub filters: PullRequestFilters,
+    pub host_type: Option<SupportedHost>,
+    pub api_url: Option<String>,
+    pub graphql: bool,
+    pub cache: bool,
+    pub refresh: bool,
+    pub offline: bool,
+    pub pull_request_refs: bool,
+    pub merge_messages: bool,
+    pub merge_message_patterns: Vec<String>,
+    pub pull_requests_file: Option<PathBuf>,
+    pub auth: AuthOptions,
+    pub bisect_in_progress: BisectInProgress,
     pub dry_run: bool,
 }
 
@@ -47,8 +79,22 @@ impl SkipPullRequestsConfigBuilder {
         Self {
             remote_url: None,
             directory: None,
-            good: None,
+            good: vec![],
             bad: None,
+            base_branch: None,
+            filters: PullRequestFilters::default(),
+            host_type: None,
+            api_url: None,
+            graphql: false,
+            cache: false,
+            refresh: false,
+            offline: false,
+            pull_request_refs: false,
+            merge_messages: false,
+            merge_message_patterns: vec![],
+            pull_requests_file: None,
+            auth: AuthOptions::default(),
+            bisect_in_progress: BisectInProgress::default(),
             dry_run: false,
         }
     }
@@ -63,7 +109,7 @@ impl SkipPullRequestsConfigBuilder {
         self
     }
 
-    pub fn good(mut self, good: Option<String>) -> Self {
+    pub fn good(mut self, good: Vec<String>) -> Self {
         self.good = good;
         self
     }
@@ -73,6 +119,76 @@ impl SkipPullRequestsConfigBuilder {
         self
     }
 
+    pub fn base_branch(mut self, base_branch: Option<String>) -> Self {
+        self.base_branch = base_branch;
+        self
+    }
+
+    pub fn filters(mut self, filters: PullRequestFilters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub fn host_type(mut self, host_type: Option<SupportedHost>) -> Self {
+        self.host_type = host_type;
+        self
+    }
+
+    pub fn api_url(mut self, api_url: Option<String>) -> Self {
+        self.api_url = api_url;
+        self
+    }
+
+    pub fn graphql(mut self, graphql: bool) -> Self {
+        self.graphql = graphql;
+        self
+    }
+
+    pub fn cache(mut self, cache: bool) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    pub fn pull_request_refs(mut self, pull_request_refs: bool) -> Self {
+        self.pull_request_refs = pull_request_refs;
+        self
+    }
+
+    pub fn merge_messages(mut self, merge_messages: bool) -> Self {
+        self.merge_messages = merge_messages;
+        self
+    }
+
+    pub fn merge_message_patterns(mut self, merge_message_patterns: Vec<String>) -> Self {
+        self.merge_message_patterns = merge_message_patterns;
+        self
+    }
+
+    pub fn pull_requests_file(mut self, pull_requests_file: Option<PathBuf>) -> Self {
+        self.pull_requests_file = pull_requests_file;
+        self
+    }
+
+    pub fn auth(mut self, auth: AuthOptions) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn bisect_in_progress(mut self, bisect_in_progress: BisectInProgress) -> Self {
+        self.bisect_in_progress = bisect_in_progress;
+        self
+    }
+
     pub fn dry_run(mut self, dry_run: bool) -> Self {
         self.dry_run = dry_run;
         self
@@ -86,6 +202,22 @@ impl SkipPullRequestsConfigBuilder {
 
         let repository_handle = GitRepository::open(&directory)?;
 
+        // A bisect in progress gets dealt with upfront, so that none of its marks get used
+        // (and nothing gets prompted for) in case it's about to be reset, or refused:
+        let is_bisecting = is_bisecting(&repository_handle);
+
+        match self.bisect_in_progress {
+            BisectInProgress::Refuse if is_bisecting => {
+                anyhow::bail!("A bisect is already in progress, pass `--reset` to reset it first");
+            }
+            // A dry run leaves the reset to the command, which merely prints it:
+            BisectInProgress::Reset if is_bisecting && !self.dry_run => {
+                eprintln!("Resetting bisect in progress ...");
+                run_git_bisect(&directory, &["reset"], false)?;
+            }
+            _ => {}
+        }
+
         let remotes: Vec<GitRemote<'_>> = repository_handle
             .remotes()?
             .into_iter()
@@ -100,8 +232,14 @@ impl SkipPullRequestsConfigBuilder {
             })
             .collect();
 
+        // Prompts would wait for input forever without a terminal (e.g. in CI):
+        let interactive = self.auth.is_interactive();
+
         let url = match self.remote_url {
             Some(repository_url) => repository_url,
+            None if !interactive => {
+                anyhow::bail!("No remote url given, pass `--remote-url <REMOTE_URL>` to pick one")
+            }
             None => {
                 let mut choices: Vec<_> = remotes.iter().map(RepositoryUrlChoice::Remote).collect();
                 choices.push(RepositoryUrlChoice::Custom);
@@ -120,20 +258,85 @@ impl SkipPullRequestsConfigBuilder {
 
         let repository = GitRepositoryUrl::from_str(&url)?;
 
-        let good: String = match self.good {
-            Some(good) => good,
-            None => Text::new("Known good commit:").prompt()?,
+        // Command-line arguments take precedence over any host mapping configured via git config:
+        let host_mapping = match repository.parsed_url.host() {
+            Some(hostname) => host_mapping(&repository_handle, hostname)?,
+            None => HostMapping::default(),
+        };
+
+        let host_type = self.host_type.or(host_mapping.host_type);
+
+        let api_url = self
+            .api_url
+            .or(host_mapping.api_url)
+            .map(|api_url| Url::parse(&api_url))
+            .transpose()?;
+
+        // Any marks of a bisect that's already in progress take precedence over prompting:
+        let bisect_marks = match self.bisect_in_progress {
+            BisectInProgress::Continue => bisect_marks(&repository_handle)?.unwrap_or_default(),
+            BisectInProgress::Refuse | BisectInProgress::Reset => Default::default(),
+        };
+
+        let good: Vec<String> = if !self.good.is_empty() {
+            self.good
+        } else if !bisect_marks.good.is_empty() {
+            eprintln!("Using good commits of bisect in progress ...");
+            bisect_marks.good.iter().map(ToString::to_string).collect()
+        } else if !interactive {
+            anyhow::bail!("No known good commit given, pass `--good <GOOD>` to pick one");
+        } else {
+            Text::new("Known good commit(s):")
+                .with_help_message("Separate multiple commits by whitespace")
+                .prompt()?
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect()
+        };
+
+        let good: Vec<String> = good
+            .iter()
+            .map(|good| good.trim().to_owned())
+            .filter(|good| !good.is_empty())
+            .collect();
+
+        if good.is_empty() {
+            anyhow::bail!("At least one known good commit is required");
         }
-        .trim()
-        .to_owned();
 
-        let bad: String = match self.bad {
-            Some(bad) => bad,
-            None => Text::new("Known bad commit:").prompt()?,
+        let bad: String = match (self.bad, bisect_marks.bad) {
+            (Some(bad), _) => bad,
+            (None, Some(bad)) => {
+                eprintln!("Using bad commit of bisect in progress ...");
+                bad.to_string()
+            }
+            (None, None) if !interactive => {
+                anyhow::bail!("No known bad commit given, pass `--bad <BAD>` to pick one")
+            }
+            (None, None) => Text::new("Known bad commit:").prompt()?,
         }
         .trim()
         .to_owned();
 
+        let base_branch = match self.base_branch {
+            Some(base_branch) => Some(base_branch),
+            None => branch_containing(&repository_handle, &bad, interactive)?,
+        };
+
+        let filters = self.filters;
+        let graphql = self.graphql;
+        let cache_directory = repository_handle
+            .path()
+            .join("assist")
+            .join("pull-requests");
+        let cache = self.cache;
+        let refresh = self.refresh;
+        let offline = self.offline;
+        let pull_request_refs = self.pull_request_refs;
+        let merge_messages = self.merge_messages;
+        let merge_message_patterns = self.merge_message_patterns;
+        let pull_requests_file = self.pull_requests_file;
+        let auth = self.auth;
         let dry_run = self.dry_run;
 
         Ok(SkipPullRequestsConfig {
@@ -141,7 +344,113 @@ impl SkipPullRequestsConfigBuilder {
             directory,
             good,
             bad,
+            base_branch,
+            filters,
+            host_type,
+            api_url,
+            graphql,
+            cache_directory,
+            cache,
+            refresh,
+            offline,
+            pull_request_refs,
+            merge_messages,
+            merge_message_patterns,
+            pull_requests_file,
+            auth,
             dry_run,
         })
     }
 }
+
+/// The host type and API url configured for a hostname.
+#[derive(Default)]
+struct HostMapping {
+    host_type: Option<SupportedHost>,
+    api_url: Option<String>,
+}
+
+/// Reads the host mapping configured for the hostname via git config
+/// (i.e. `assist.host.<hostname>.type` and `assist.host.<hostname>.apiUrl`).
+fn host_mapping(repository: &GitRepository, hostname: &str) -> anyhow::Result<HostMapping> {
+    let host_type = config_string(repository, &format!("assist.host.{hostname}.type"))?
+        .map(|host_type| SupportedHost::from_str(&host_type))
+        .transpose()?;
+
+    let api_url = config_string(repository, &format!("assist.host.{hostname}.apiUrl"))?;
+
+    Ok(HostMapping { host_type, api_url })
+}
+
+/// Detects the branch that contains the given commit, to be used as base branch.
+///
+/// Both, local and remote-tracking branches are considered, with the latter
+/// referred to by their name on the remote. If there are multiple candidates,
+/// a remote's default branch is preferred, otherwise the user gets prompted,
+/// unless prompting isn't allowed (i.e. not `interactive`), which is an error.
+fn branch_containing(
+    repository: &GitRepository,
+    commit: &str,
+    interactive: bool,
+) -> anyhow::Result<Option<String>> {
+    let commit_id = repository.revparse_single(commit)?.peel_to_commit()?.id();
+
+    let mut candidates: Vec<String> = vec![];
+
+    for branch in repository.branches(None)? {
+        let (branch, branch_type) = branch?;
+
+        let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) else {
+            continue;
+        };
+
+        let name = match branch_type {
+            GitBranchType::Local => name,
+            GitBranchType::Remote => match name.split_once('/') {
+                Some((_, "HEAD")) | None => continue,
+                Some((_remote, name)) => name,
+            },
+        };
+
+        let contains_commit = tip == commit_id || repository.graph_descendant_of(tip, commit_id)?;
+
+        if contains_commit && !candidates.iter().any(|candidate| candidate == name) {
+            candidates.push(name.to_owned());
+        }
+    }
+
+    if candidates.len() <= 1 {
+        if candidates.is_empty() {
+            eprintln!("Warning: No branch contains {commit}, not restricting base branch");
+        }
+
+        return Ok(candidates.pop());
+    }
+
+    let default_branches: Vec<String> = repository
+        .references_glob("refs/remotes/*/HEAD")?
+        .filter_map(|reference| {
+            let target = reference.ok()?.symbolic_target()?.to_owned();
+            let (_remote, name) = target.strip_prefix("refs/remotes/")?.split_once('/')?;
+            Some(name.to_owned())
+        })
+        .collect();
+
+    if let Some(default_branch) = candidates
+        .iter()
+        .find(|candidate| default_branches.contains(candidate))
+    {
+        return Ok(Some(default_branch.clone()));
+    }
+
+    if !interactive {
+        anyhow::bail!(
+            "Multiple branches contain {commit} ({candidates}), pass `--base-branch <BASE_BRANCH>` to pick one",
+            candidates = candidates.join(", ")
+        );
+    }
+
+    let base_branch = Select::new("Base branch:", candidates).prompt()?;
+
+    Ok(Some(base_branch))
+}
@@ -1,20 +1,38 @@
+mod by_pull_request;
 mod config;
+mod run;
+mod search;
+mod start;
+mod strategy;
 
 use std::{
-    collections::HashSet,
-    os::unix::process::ExitStatusExt,
-    path::PathBuf,
-    process::{Command, ExitStatus},
+    collections::HashSet, os::unix::process::ExitStatusExt, path::PathBuf, process::ExitStatus,
 };
 
-use git2::{Oid, Repository as GitRepository};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use futures::StreamExt;
+use git2::{Error as GitError, Oid, Repository as GitRepository};
+use url::Url;
 
 use crate::{
-    git::commits_in_range,
-    host::{GitHost, GitPullRequest, GitRepositoryUrl},
+    git::{commits_between, commits_in_range, skip_commits},
+    host::{
+        AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl, PullRequestFilters,
+        PullRequestQuery, SupportedHost,
+    },
 };
 
-pub use config::SkipPullRequestsConfigBuilder;
+pub use self::{
+    by_pull_request::bisect_by_pull_request,
+    config::{BisectInProgress, SkipPullRequestsConfigBuilder},
+    run::bisect_run,
+    search::{BisectOutcome, Verdict},
+    start::start_bisect,
+    strategy::{MergeStrategy, SkipRange},
+};
+
+/// The maximum number of pull requests processed at once, as they arrive.
+const CHUNK_SIZE: usize = 100;
 
 pub struct SkipPullRequestsConfig {
     /// The git repository.
@@ -23,16 +41,73 @@ pub struct SkipPullRequestsConfig {
     // The git directory.
     pub directory: PathBuf,
 
-    /// A known "good" git commit.
-    pub good: String,
+    /// The known "good" git commits.
+    pub good: Vec<String>,
 
     /// A known "bad" git commit.
     pub bad: String,
 
+    /// The branch to restrict pull requests to, by their base branch.
+    pub base_branch: Option<String>,
+
+    /// The filters for selecting pull requests to skip.
+    pub filters: PullRequestFilters,
+
+    /// The type of the repository's host, unless to be detected.
+    pub host_type: Option<SupportedHost>,
+
+    /// The url of the host's API, unless to be derived from the repository's url.
+    pub api_url: Option<Url>,
+
+    /// Request pull requests via the host's GraphQL API, instead of its REST API.
+    pub graphql: bool,
+
+    /// The directory to cache pull requests in.
+    pub cache_directory: PathBuf,
+
+    /// Cache pull requests, so that later runs only request those merged since.
+    pub cache: bool,
+
+    /// Discard any cached pull requests, requesting them all over again (implies `cache`).
+    pub refresh: bool,
+
+    /// Only use cached pull requests, without requesting any from the host.
+    pub offline: bool,
+
+    /// Derive merged pull requests from fetched `refs/pull/*/head` refs, instead of requesting them from the host.
+    pub pull_request_refs: bool,
+
+    /// Reconstruct merged pull requests from the messages of merge commits, instead of requesting them from the host.
+    pub merge_messages: bool,
+
+    /// The patterns recognizing merge commit messages, unless to use the default ones.
+    pub merge_message_patterns: Vec<String>,
+
+    /// The JSON file to load pull requests from, instead of requesting them from the host.
+    pub pull_requests_file: Option<PathBuf>,
+
+    /// The options for authenticating with the host.
+    pub auth: AuthOptions,
+
     /// Perform a "dry" run.
     pub dry_run: bool,
 }
 
+/// A merged pull request within the bisected range, classified by its merge strategy.
+pub struct ClassifiedPullRequest {
+    /// The pull request.
+    pub pull_request: GitPullRequest,
+
+    /// The pull request's merge commit.
+    pub merge_commit: Oid,
+
+    /// The strategy the pull request got merged with.
+    pub strategy: MergeStrategy,
+
+    /// The range of the pull request's internal commits, if there are any.
+    pub skip_range: Option<SkipRange>,
+}
+
 pub async fn skip_pull_requests(
     host: &dyn GitHost,
     config: &SkipPullRequestsConfig,
@@ -40,67 +115,275 @@ pub async fn skip_pull_requests(
     eprintln!("Opening git repository ...");
     let repository = GitRepository::open(&config.directory)?;
 
-    let range_commit_ids: HashSet<Oid> = {
-        let range = (
-            repository.revparse_single(&config.good)?.id(),
-            repository.revparse_single(&config.bad)?.id(),
-        );
-        commits_in_range(&repository, range)?
-            .into_iter()
-            .map(|commit| commit.id())
-            .collect()
+    skip_pull_requests_in(host, config, &repository).await?;
+
+    Ok(ExitStatus::from_raw(0))
+}
+
+/// Skips the internal commits of all pull requests within the config's range that match its filters,
+/// returning all classified pull requests (filtered out, or not), together with the skipped commits.
+async fn skip_pull_requests_in(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    repository: &GitRepository,
+) -> anyhow::Result<(Vec<ClassifiedPullRequest>, HashSet<Oid>)> {
+    let (good, bad) = resolve_range(repository, config)?;
+
+    let mut pull_requests: Vec<ClassifiedPullRequest> = vec![];
+    let mut skipped_commit_ids: HashSet<Oid> = HashSet::new();
+
+    // Skips get applied chunk by chunk, as the pull requests arrive:
+    visit_pull_requests_in_range(host, config, repository, (&good, bad), |chunk| {
+        let mut chunk_commit_ids: HashSet<Oid> = HashSet::new();
+
+        for classified in &chunk {
+            // Filtered-out pull requests still count as merged within the range, they just don't get skipped:
+            if !config.filters.matches(&classified.pull_request) {
+                continue;
+            }
+
+            print_pull_request(classified);
+
+            let Some(skip_range) = classified.skip_range else {
+                continue;
+            };
+
+            if config.dry_run {
+                println!("git bisect skip {skip_range}");
+            }
+
+            chunk_commit_ids.extend(commits_between(
+                repository,
+                (skip_range.start, skip_range.end),
+            )?);
+        }
+
+        if !config.dry_run && !chunk_commit_ids.is_empty() {
+            eprintln!(
+                "Skipping {count} commits ...",
+                count = chunk_commit_ids.len()
+            );
+            skip_commits(repository, chunk_commit_ids.iter().copied())?;
+        }
+
+        pull_requests.extend(chunk);
+        skipped_commit_ids.extend(chunk_commit_ids);
+
+        Ok(())
+    })
+    .await?;
+
+    Ok((pull_requests, skipped_commit_ids))
+}
+
+/// Resolves the config's good and bad commits within the repository.
+pub(crate) fn resolve_range(
+    repository: &GitRepository,
+    config: &SkipPullRequestsConfig,
+) -> anyhow::Result<(Vec<Oid>, Oid)> {
+    let good: Vec<Oid> = config
+        .good
+        .iter()
+        .map(|good| Ok(repository.revparse_single(good)?.peel_to_commit()?.id()))
+        .collect::<Result<_, GitError>>()?;
+    let bad = repository
+        .revparse_single(&config.bad)?
+        .peel_to_commit()?
+        .id();
+
+    Ok((good, bad))
+}
+
+/// Returns the time window within which pull requests of the `good..bad` range
+/// must have been merged, derived from the committer dates of `good` and `bad`.
+///
+/// The window is widened by a day on either side, to account for clock skew
+/// and for delays between committing and merging.
+fn merge_time_window(
+    repository: &GitRepository,
+    range: (&[Oid], Oid),
+) -> anyhow::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let committed_at = |commit_id: Oid| -> anyhow::Result<DateTime<Utc>> {
+        let seconds = repository
+            .find_commit(commit_id)?
+            .committer()
+            .when()
+            .seconds();
+
+        Utc.timestamp_opt(seconds, 0)
+            .single()
+            .ok_or_else(|| anyhow::anyhow!("Invalid commit date of {commit_id}"))
     };
 
-    eprintln!("Requesting pull requests ...");
-    let pull_requests = host.merged_pull_requests(&config.repository).await?;
+    let tolerance = Duration::days(1);
 
-    eprintln!("Filtering pull requests ...");
-    let pull_requests: Vec<GitPullRequest> = pull_requests
+    let earliest_good = range
+        .0
+        .iter()
+        .map(|&good| committed_at(good))
+        .collect::<anyhow::Result<Vec<_>>>()?
         .into_iter()
-        .filter(|pull_request| {
-            let Ok(base_obj) = repository.revparse_single(&pull_request.base_sha) else {
-                return false;
-            };
-            let Ok(merge_obj) = repository.revparse_single(&pull_request.merge_sha) else {
-                return false;
-            };
+        .min();
+
+    let bad = committed_at(range.1)?;
+
+    let merged_after = earliest_good.unwrap_or(bad) - tolerance;
+    let merged_before = bad + tolerance;
+
+    Ok((merged_after, merged_before))
+}
+
+/// Returns the query for the pull requests that may overlap with the `good..bad` range.
+pub(crate) fn pull_request_query(
+    config: &SkipPullRequestsConfig,
+    repository: &GitRepository,
+    range: (&[Oid], Oid),
+) -> anyhow::Result<PullRequestQuery> {
+    let (merged_after, merged_before) = merge_time_window(repository, range)?;
+
+    Ok(PullRequestQuery {
+        base_ref: config.base_branch.clone(),
+        merged_after: Some(merged_after),
+        merged_before: Some(merged_before),
+    })
+}
 
-            range_commit_ids.contains(&base_obj.id()) || range_commit_ids.contains(&merge_obj.id())
-        })
+/// Requests the host's merged pull requests that overlap with the `good..bad` range
+/// and classifies them by their merge strategy.
+async fn pull_requests_in_range(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    repository: &GitRepository,
+    range: (&[Oid], Oid),
+) -> anyhow::Result<Vec<ClassifiedPullRequest>> {
+    let mut pull_requests: Vec<ClassifiedPullRequest> = vec![];
+
+    visit_pull_requests_in_range(host, config, repository, range, |chunk| {
+        pull_requests.extend(chunk);
+        Ok(())
+    })
+    .await?;
+
+    Ok(pull_requests)
+}
+
+/// Streams the host's merged pull requests that overlap with the `good..bad` range,
+/// classifies them by their merge strategy, and passes them to `visit`
+/// in chunks, as they arrive.
+async fn visit_pull_requests_in_range(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    repository: &GitRepository,
+    range: (&[Oid], Oid),
+    mut visit: impl FnMut(Vec<ClassifiedPullRequest>) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let range_commit_ids: HashSet<Oid> = commits_in_range(repository, range)?
+        .into_iter()
+        .map(|commit| commit.id())
         .collect();
 
-    if !config.dry_run && std::env::current_dir()? != config.directory {
-        eprintln!("Entering repository directory ...");
-        std::env::set_current_dir(&config.directory)?;
-    }
+    let query = pull_request_query(config, repository, range)?;
 
-    for pull_request in pull_requests {
-        let program = "git";
-        let mut command = Command::new(program);
-        let mut args = vec!["bisect".to_owned(), "skip".to_owned()];
+    eprintln!("Requesting pull requests ...");
+    let mut chunks = host
+        .merged_pull_requests(&config.repository, &query)
+        .ready_chunks(CHUNK_SIZE);
 
-        args.push(format!(
-            "{start}..{end}^",
-            start = pull_request.base_sha,
-            end = pull_request.merge_sha,
-        ));
+    let mut requested_count = 0;
+    let mut in_range_count = 0;
 
-        let formatted_args = args.join(" ");
+    while let Some(chunk) = chunks.next().await {
+        let mut classified_chunk: Vec<ClassifiedPullRequest> = vec![];
 
-        command.args(args);
+        for pull_request in chunk {
+            requested_count += 1;
 
-        println!(
-            "# Pull request #{number}: {title:?}",
-            number = pull_request.identifier,
-            title = pull_request.title
-        );
+            if let Some(classified) =
+                classify_in_range(repository, config, &range_commit_ids, pull_request?)?
+            {
+                classified_chunk.push(classified);
+            }
+        }
 
-        if config.dry_run {
-            println!("{program} {formatted_args}");
-        } else {
-            command.spawn().and_then(|mut child| child.wait())?;
+        in_range_count += classified_chunk.len();
+
+        eprintln!("Requested {requested_count} pull requests, {in_range_count} within range ...");
+
+        visit(classified_chunk)?;
+    }
+
+    Ok(())
+}
+
+/// Classifies the pull request by its merge strategy,
+/// unless it doesn't overlap with the range.
+fn classify_in_range(
+    repository: &GitRepository,
+    config: &SkipPullRequestsConfig,
+    range_commit_ids: &HashSet<Oid>,
+    pull_request: GitPullRequest,
+) -> anyhow::Result<Option<ClassifiedPullRequest>> {
+    if let Some(base_branch) = &config.base_branch {
+        if &pull_request.base_ref != base_branch {
+            return Ok(None);
         }
     }
 
-    Ok(ExitStatus::from_raw(0))
+    let Ok(merge_commit) = repository
+        .revparse_single(&pull_request.merge_sha)
+        .and_then(|object| object.peel_to_commit())
+    else {
+        return Ok(None);
+    };
+    let base = match &pull_request.base_sha {
+        Some(base_sha) => match repository.revparse_single(base_sha) {
+            Ok(base) => Some(base.id()),
+            Err(_) => return Ok(None),
+        },
+        None => None,
+    };
+
+    let overlaps = base
+        .or_else(|| merge_commit.parent_id(0).ok())
+        .is_some_and(|base| range_commit_ids.contains(&base))
+        || range_commit_ids.contains(&merge_commit.id());
+
+    if !overlaps {
+        return Ok(None);
+    }
+
+    let (strategy, skip_range) = MergeStrategy::classify(&merge_commit, base)?;
+
+    Ok(Some(ClassifiedPullRequest {
+        pull_request,
+        merge_commit: merge_commit.id(),
+        strategy,
+        skip_range,
+    }))
+}
+
+/// Returns the first-parent commits within the `good..bad` range,
+/// ordered from oldest to newest, excluding `bad` itself.
+fn first_parents_in_range(
+    repository: &GitRepository,
+    range: (&[Oid], Oid),
+) -> anyhow::Result<Vec<Oid>> {
+    let mut commit_ids: Vec<Oid> = commits_in_range(repository, range)?
+        .into_iter()
+        .map(|commit| commit.id())
+        .filter(|&commit_id| commit_id != range.1)
+        .collect();
+
+    commit_ids.reverse();
+
+    Ok(commit_ids)
+}
+
+fn print_pull_request(classified: &ClassifiedPullRequest) {
+    println!(
+        "# Pull request #{number} ({strategy}): {title:?}",
+        number = classified.pull_request.identifier,
+        strategy = classified.strategy,
+        title = classified.pull_request.title
+    );
 }
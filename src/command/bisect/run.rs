@@ -0,0 +1,159 @@
+use std::{
+    os::unix::process::ExitStatusExt,
+    process::{Command, ExitStatus},
+};
+
+use git2::{Oid, Repository as GitRepository};
+
+use crate::{
+    git::{is_bisecting, mark_commits, BisectMark},
+    host::GitHost,
+};
+
+use super::{
+    first_parents_in_range, print_pull_request, resolve_range,
+    search::{bisect_linearly, BisectOutcome, Verdict},
+    skip_pull_requests_in,
+    start::begin_bisect,
+    ClassifiedPullRequest, SkipPullRequestsConfig,
+};
+
+/// The exit code with which a test command signals that the commit cannot be tested.
+const EXIT_CODE_SKIP: i32 = 125;
+
+/// Bisects automatically by running the given test command on each commit under test,
+/// with all internal pull request commits skipped.
+///
+/// Just like with `git bisect run` the command's exit code determines the verdict:
+///
+/// - `0`: good
+/// - `125`: skip
+/// - `1..=127` (except `125`): bad
+///
+/// Any other exit code (or termination by a signal) aborts the bisect.
+///
+/// The bisect in progress is continued if there is one, otherwise a new one is started.
+pub async fn bisect_run(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    command: &[String],
+) -> anyhow::Result<ExitStatus> {
+    let Some((program, args)) = command.split_first() else {
+        anyhow::bail!("No command specified");
+    };
+
+    eprintln!("Opening git repository ...");
+    let repository = GitRepository::open(&config.directory)?;
+
+    let (good, bad) = resolve_range(&repository, config)?;
+
+    if !config.dry_run && !is_bisecting(&repository) {
+        begin_bisect(&repository, &config.directory, false, false)?;
+    }
+
+    let (pull_requests, skipped_commit_ids) =
+        skip_pull_requests_in(host, config, &repository).await?;
+
+    let candidates: Vec<Oid> = first_parents_in_range(&repository, (&good, bad))?
+        .into_iter()
+        .filter(|commit_id| !skipped_commit_ids.contains(commit_id))
+        .collect();
+
+    if config.dry_run {
+        println!(
+            "# Bisecting {count} commits, running: {command:?}",
+            count = candidates.len()
+        );
+
+        return Ok(ExitStatus::from_raw(0));
+    }
+
+    mark_commits(&repository, BisectMark::Good, good.iter().copied())?;
+    mark_commits(&repository, BisectMark::Bad, [bad])?;
+
+    eprintln!("Bisecting {count} commits ...", count = candidates.len());
+
+    let outcome = bisect_linearly(&repository, candidates, bad, |_commit| {
+        run_test_command(&repository, program, args)
+    })?;
+
+    match outcome {
+        BisectOutcome::Found { first_bad, .. } => {
+            let commit = repository.find_commit(first_bad)?;
+
+            println!(
+                "{first_bad} is the first bad commit: {summary:?}",
+                summary = commit.summary().unwrap_or_default()
+            );
+
+            match pull_request_of_commit(&repository, &pull_requests, first_bad)? {
+                Some(classified) => print_pull_request(classified),
+                None => println!("# Not part of any pull request"),
+            }
+        }
+        BisectOutcome::Ambiguous { candidates } => {
+            println!("There are only skipped commits left to test.");
+            println!("The first bad commit could be any of:");
+
+            for candidate in candidates {
+                println!("{candidate}");
+            }
+        }
+    }
+
+    Ok(ExitStatus::from_raw(0))
+}
+
+fn run_test_command(
+    repository: &GitRepository,
+    program: &str,
+    args: &[String],
+) -> anyhow::Result<Verdict> {
+    let directory = repository
+        .workdir()
+        .ok_or_else(|| anyhow::anyhow!("Cannot bisect a bare repository"))?;
+
+    eprintln!("Running {program} {args}", args = args.join(" "));
+
+    let status = Command::new(program)
+        .args(args)
+        .current_dir(directory)
+        .status()?;
+
+    match status.code() {
+        Some(0) => Ok(Verdict::Good),
+        Some(EXIT_CODE_SKIP) => Ok(Verdict::Skip),
+        Some(1..=127) => Ok(Verdict::Bad),
+        _ => anyhow::bail!("Bisect run failed: {program} exited with {status}"),
+    }
+}
+
+/// Returns the pull request that the given commit got merged with, if any.
+fn pull_request_of_commit<'a>(
+    repository: &GitRepository,
+    pull_requests: &'a [ClassifiedPullRequest],
+    commit_id: Oid,
+) -> anyhow::Result<Option<&'a ClassifiedPullRequest>> {
+    for classified in pull_requests {
+        if classified.merge_commit == commit_id {
+            return Ok(Some(classified));
+        }
+
+        let Some(skip_range) = classified.skip_range else {
+            continue;
+        };
+
+        let is_reachable_from_end = commit_id == skip_range.end
+            || repository.graph_descendant_of(skip_range.end, commit_id)?;
+        let is_reachable_from_start = commit_id == skip_range.start
+            || repository.graph_descendant_of(skip_range.start, commit_id)?;
+
+        let is_internal = is_reachable_from_end && !is_reachable_from_start;
+
+        if is_internal {
+            return Ok(Some(classified));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,326 @@
+use std::{collections::HashSet, fmt};
+
+use git2::{Commit as GitCommit, Oid, Repository as GitRepository};
+
+use crate::git::{checkout_detached, mark_commits, BisectMark};
+
+/// A verdict on a commit under test.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Verdict {
+    /// The commit does not exhibit the bug.
+    Good,
+
+    /// The commit exhibits the bug.
+    Bad,
+
+    /// The commit cannot be tested.
+    Skip,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Good => write!(f, "good"),
+            Self::Bad => write!(f, "bad"),
+            Self::Skip => write!(f, "skip"),
+        }
+    }
+}
+
+impl From<Verdict> for BisectMark {
+    fn from(verdict: Verdict) -> Self {
+        match verdict {
+            Verdict::Good => Self::Good,
+            Verdict::Bad => Self::Bad,
+            Verdict::Skip => Self::Skip,
+        }
+    }
+}
+
+/// The outcome of a bisection.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum BisectOutcome {
+    /// The first bad commit got found.
+    Found {
+        /// The first bad commit.
+        first_bad: Oid,
+
+        /// The last good commit preceding the first bad commit,
+        /// if it was among the bisected candidates.
+        last_good: Option<Oid>,
+    },
+
+    /// The first bad commit could not be determined due to skipped commits,
+    /// but is known to be one of the given commits.
+    Ambiguous {
+        /// The commits that could be the first bad commit.
+        candidates: Vec<Oid>,
+    },
+}
+
+/// A binary search over a linear sequence of candidate commits,
+/// preceded by known good commits and followed by a known bad commit.
+#[derive(Clone, Debug)]
+struct LinearBisection {
+    /// The candidates, ordered from oldest to newest.
+    candidates: Vec<Oid>,
+
+    /// The known bad commit, which implicitly follows the last candidate.
+    bad: Oid,
+
+    /// The index of the first candidate not known to be good.
+    lower: usize,
+
+    /// The index of the first candidate known to be bad
+    /// (with `candidates.len()` referring to `bad`).
+    upper: usize,
+
+    /// The indices of the candidates that got skipped.
+    skipped: HashSet<usize>,
+}
+
+impl LinearBisection {
+    fn new(candidates: Vec<Oid>, bad: Oid) -> Self {
+        let upper = candidates.len();
+
+        Self {
+            candidates,
+            bad,
+            lower: 0,
+            upper,
+            skipped: HashSet::new(),
+        }
+    }
+
+    fn commit_id(&self, index: usize) -> Oid {
+        self.candidates.get(index).copied().unwrap_or(self.bad)
+    }
+
+    /// Returns the index of the untested candidate closest to the middle of the range, if any.
+    fn next_index(&self) -> Option<usize> {
+        let middle = self.lower + (self.upper - self.lower) / 2;
+
+        (0..=(self.upper - self.lower))
+            .flat_map(|distance| [middle.checked_sub(distance), middle.checked_add(distance)])
+            .flatten()
+            .find(|index| (self.lower..self.upper).contains(index) && !self.skipped.contains(index))
+    }
+
+    /// Returns the number of untested candidates.
+    fn remaining(&self) -> usize {
+        (self.lower..self.upper)
+            .filter(|index| !self.skipped.contains(index))
+            .count()
+    }
+
+    fn mark(&mut self, index: usize, verdict: Verdict) {
+        match verdict {
+            Verdict::Good => self.lower = index + 1,
+            Verdict::Bad => self.upper = index,
+            Verdict::Skip => {
+                self.skipped.insert(index);
+            }
+        }
+    }
+
+    fn outcome(&self) -> BisectOutcome {
+        if self.lower == self.upper {
+            BisectOutcome::Found {
+                first_bad: self.commit_id(self.upper),
+                last_good: self.lower.checked_sub(1).map(|index| self.commit_id(index)),
+            }
+        } else {
+            BisectOutcome::Ambiguous {
+                candidates: (self.lower..=self.upper)
+                    .map(|index| self.commit_id(index))
+                    .collect(),
+            }
+        }
+    }
+}
+
+/// Bisects the given candidates, ordered from oldest to newest, which are
+/// preceded by the session's known good commits and followed by its known `bad` commit.
+///
+/// Each candidate gets checked out and judged, with its verdict getting recorded
+/// in the `git bisect` session in progress.
+pub(crate) fn bisect_linearly(
+    repository: &GitRepository,
+    candidates: Vec<Oid>,
+    bad: Oid,
+    mut judge: impl FnMut(&GitCommit<'_>) -> anyhow::Result<Verdict>,
+) -> anyhow::Result<BisectOutcome> {
+    let mut bisection = LinearBisection::new(candidates, bad);
+
+    while let Some(index) = bisection.next_index() {
+        let commit_id = bisection.commit_id(index);
+        let commit = repository.find_commit(commit_id)?;
+
+        eprintln!(
+            "Bisecting: {count} revisions left to test after this",
+            count = bisection.remaining() - 1
+        );
+
+        checkout_detached(repository, commit_id)?;
+
+        println!(
+            "[{commit_id}] {summary}",
+            summary = commit.summary().unwrap_or_default()
+        );
+
+        let verdict = judge(&commit)?;
+
+        mark_commits(repository, verdict.into(), [commit_id])?;
+        bisection.mark(index, verdict);
+    }
+
+    Ok(bisection.outcome())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(index: usize) -> Oid {
+        Oid::from_str(&format!("{index:040x}")).unwrap()
+    }
+
+    /// Returns `count` candidates, followed by the known bad commit.
+    fn commits(count: usize) -> (Vec<Oid>, Oid) {
+        ((0..count).map(oid).collect(), oid(count))
+    }
+
+    /// Bisects the candidates with the given verdicts by index,
+    /// returning the outcome along with the indices of the tested candidates, in order.
+    fn bisect(
+        candidates: Vec<Oid>,
+        bad: Oid,
+        verdict: impl Fn(usize) -> Verdict,
+    ) -> (BisectOutcome, Vec<usize>) {
+        let mut bisection = LinearBisection::new(candidates, bad);
+        let mut tested = vec![];
+
+        while let Some(index) = bisection.next_index() {
+            tested.push(index);
+            bisection.mark(index, verdict(index));
+        }
+
+        (bisection.outcome(), tested)
+    }
+
+    #[test]
+    fn all_good() {
+        let (candidates, bad) = commits(5);
+
+        let (outcome, tested) = bisect(candidates, bad, |_| Verdict::Good);
+
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found {
+                first_bad: bad,
+                last_good: Some(oid(4)),
+            }
+        );
+        assert_eq!(tested, [2, 4]);
+    }
+
+    #[test]
+    fn all_bad() {
+        let (candidates, bad) = commits(5);
+
+        let (outcome, tested) = bisect(candidates, bad, |_| Verdict::Bad);
+
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found {
+                first_bad: oid(0),
+                last_good: None,
+            }
+        );
+        assert_eq!(tested, [2, 1, 0]);
+    }
+
+    #[test]
+    fn skipped_middle() {
+        let (candidates, bad) = commits(5);
+
+        let (outcome, tested) = bisect(candidates, bad, |index| match index {
+            2 => Verdict::Skip,
+            index if index < 4 => Verdict::Good,
+            _ => Verdict::Bad,
+        });
+
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found {
+                first_bad: oid(4),
+                last_good: Some(oid(3)),
+            }
+        );
+
+        // The candidates next to the skipped middle get tested instead:
+        assert_eq!(tested, [2, 1, 3, 4]);
+    }
+
+    #[test]
+    fn single_candidate() {
+        let (candidates, bad) = commits(1);
+
+        let (outcome, tested) = bisect(candidates.clone(), bad, |_| Verdict::Good);
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found {
+                first_bad: bad,
+                last_good: Some(oid(0)),
+            }
+        );
+        assert_eq!(tested, [0]);
+
+        let (outcome, tested) = bisect(candidates, bad, |_| Verdict::Bad);
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found {
+                first_bad: oid(0),
+                last_good: None,
+            }
+        );
+        assert_eq!(tested, [0]);
+    }
+
+    #[test]
+    fn no_candidates() {
+        let (candidates, bad) = commits(0);
+
+        let (outcome, tested) = bisect(candidates, bad, |_| unreachable!());
+
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found {
+                first_bad: bad,
+                last_good: None,
+            }
+        );
+        assert!(tested.is_empty());
+    }
+
+    #[test]
+    fn all_remaining_skipped() {
+        let (candidates, bad) = commits(5);
+
+        let (outcome, tested) = bisect(candidates, bad, |index| match index {
+            0 => Verdict::Good,
+            _ => Verdict::Skip,
+        });
+
+        assert_eq!(
+            outcome,
+            BisectOutcome::Ambiguous {
+                candidates: vec![oid(1), oid(2), oid(3), oid(4), bad],
+            }
+        );
+
+        let mut tested = tested;
+        tested.sort();
+        assert_eq!(tested, [0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,77 @@
+use std::{os::unix::process::ExitStatusExt, path::Path, process::ExitStatus};
+
+use git2::Repository as GitRepository;
+
+use crate::{
+    git::{is_bisecting, run_git_bisect},
+    host::GitHost,
+};
+
+use super::{skip_pull_requests, SkipPullRequestsConfig};
+
+/// Starts a new `git bisect` session with all internal pull request commits skipped.
+///
+/// This is equivalent to running the following commands, in order:
+///
+/// ```terminal
+/// git bisect start
+/// git assist bisect skip-pull-requests --good <GOOD> --bad <BAD>
+/// git bisect good <GOOD>
+/// git bisect bad <BAD>
+/// ```
+///
+/// Fails if a bisect is already in progress, unless `reset` is `true`,
+/// in which case the bisect in progress gets reset first.
+pub async fn start_bisect(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    reset: bool,
+) -> anyhow::Result<ExitStatus> {
+    let directory = &config.directory;
+    let dry_run = config.dry_run;
+
+    let repository = GitRepository::open(directory)?;
+
+    begin_bisect(&repository, directory, reset, dry_run)?;
+
+    let exit_status = skip_pull_requests(host, config).await?;
+
+    if !exit_status.success() {
+        return Ok(exit_status);
+    }
+
+    eprintln!("Marking good commits ...");
+    let mut good_args = vec!["good"];
+    good_args.extend(config.good.iter().map(String::as_str));
+    run_git_bisect(directory, &good_args, dry_run)?;
+
+    eprintln!("Marking bad commit ...");
+    run_git_bisect(directory, &["bad", &config.bad], dry_run)?;
+
+    Ok(ExitStatus::from_raw(0))
+}
+
+/// Starts a new `git bisect` session.
+///
+/// Fails if a bisect is already in progress, unless `reset` is `true`,
+/// in which case the bisect in progress gets reset first.
+pub(super) fn begin_bisect(
+    repository: &GitRepository,
+    directory: &Path,
+    reset: bool,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    if is_bisecting(repository) {
+        if !reset {
+            anyhow::bail!("A bisect is already in progress, pass `--reset` to reset it first");
+        }
+
+        eprintln!("Resetting bisect in progress ...");
+        run_git_bisect(directory, &["reset"], dry_run)?;
+    }
+
+    eprintln!("Starting bisect ...");
+    run_git_bisect(directory, &["start"], dry_run)?;
+
+    Ok(())
+}
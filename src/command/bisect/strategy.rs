@@ -0,0 +1,259 @@
+use std::fmt;
+
+use git2::{Commit as GitCommit, Error as GitError, Oid};
+
+/// The scheme by which a pull request got merged into its base branch.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum MergeStrategy {
+    /// "Create a merge commit": the pull request's commits are joined
+    /// into the base branch via a commit with two parents.
+    MergeCommit,
+
+    /// "Squash and merge": the pull request's commits are collapsed
+    /// into a single commit on top of the base branch.
+    Squash,
+
+    /// "Rebase and merge": the pull request's commits are re-applied
+    /// individually on top of the base branch.
+    Rebase,
+}
+
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MergeCommit => write!(f, "merge commit"),
+            Self::Squash => write!(f, "squash"),
+            Self::Rebase => write!(f, "rebase"),
+        }
+    }
+}
+
+/// A range of pull request internal commits, as in `git bisect skip <start>..<end>`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct SkipRange {
+    /// The (excluded) commit the range starts after.
+    pub start: Oid,
+
+    /// The (included) commit the range ends with.
+    pub end: Oid,
+}
+
+impl fmt::Display for SkipRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{start}..{end}", start = self.start, end = self.end)
+    }
+}
+
+impl MergeStrategy {
+    /// Classifies a merged pull request based on the shape of the local commit graph
+    /// and returns the range of its internal commits, if there are any.
+    ///
+    /// - A merge commit with more than one parent is a `MergeCommit`,
+    ///   whose internal commits are `MERGE^1..MERGE^2`.
+    /// - A merge commit that is preceded by commits sharing its committer signature
+    ///   (which is what Github's "Rebase and merge" produces) is a `Rebase`,
+    ///   whose internal commits are those preceding commits.
+    /// - Any other merge commit is a `Squash`, which has no internal commits.
+    ///
+    /// Without a known `base`, a `Rebase`'s internal commits are only delimited by their committer signature.
+    pub fn classify(
+        merge_commit: &GitCommit<'_>,
+        base: Option<Oid>,
+    ) -> Result<(Self, Option<SkipRange>), GitError> {
+        if merge_commit.parent_count() > 1 {
+            let range = SkipRange {
+                start: merge_commit.parent_id(0)?,
+                end: merge_commit.parent_id(1)?,
+            };
+            return Ok((Self::MergeCommit, Some(range)));
+        }
+
+        let Ok(end) = merge_commit.parent(0) else {
+            return Ok((Self::Squash, None));
+        };
+
+        let mut start = end.clone();
+        let mut is_rebase = false;
+
+        while Some(start.id()) != base
+            && start.parent_count() == 1
+            && is_same_committer(&start, merge_commit)
+        {
+            start = start.parent(0)?;
+            is_rebase = true;
+        }
+
+        if !is_rebase {
+            return Ok((Self::Squash, None));
+        }
+
+        let range = SkipRange {
+            start: start.id(),
+            end: end.id(),
+        };
+
+        Ok((Self::Rebase, Some(range)))
+    }
+}
+
+fn is_same_committer(lhs: &GitCommit<'_>, rhs: &GitCommit<'_>) -> bool {
+    let (lhs, rhs) = (lhs.committer(), rhs.committer());
+
+    lhs.name_bytes() == rhs.name_bytes()
+        && lhs.email_bytes() == rhs.email_bytes()
+        && lhs.when() == rhs.when()
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::{Repository, Signature, Time};
+
+    use crate::test_support::TestDirectory;
+
+    use super::*;
+
+    /// Creates a commit on top of the parents, authored and committed by `committer` at `time`.
+    fn commit(repository: &Repository, committer: &str, time: i64, parents: &[Oid]) -> Oid {
+        let signature = Signature::new(
+            committer,
+            &format!("{}@example.com", committer.to_lowercase()),
+            &Time::new(time, 0),
+        )
+        .unwrap();
+        let tree = repository
+            .find_tree(repository.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|&parent| repository.find_commit(parent).unwrap())
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+
+        repository
+            .commit(None, &signature, &signature, "Commit", &tree, &parents)
+            .unwrap()
+    }
+
+    fn classify(
+        repository: &Repository,
+        merge_commit: Oid,
+        base: Option<Oid>,
+    ) -> (MergeStrategy, Option<SkipRange>) {
+        let merge_commit = repository.find_commit(merge_commit).unwrap();
+
+        MergeStrategy::classify(&merge_commit, base).unwrap()
+    }
+
+    #[test]
+    fn classify_merge_commit() {
+        let directory = TestDirectory::new();
+        let repository = Repository::init(directory.path()).unwrap();
+
+        let base = commit(&repository, "Dev", 100, &[]);
+        let first = commit(&repository, "Dev", 200, &[base]);
+        let second = commit(&repository, "Dev", 300, &[first]);
+        let merge = commit(&repository, "GitHub", 400, &[base, second]);
+
+        assert_eq!(
+            classify(&repository, merge, Some(base)),
+            (
+                MergeStrategy::MergeCommit,
+                Some(SkipRange {
+                    start: base,
+                    end: second
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn classify_squash() {
+        let directory = TestDirectory::new();
+        let repository = Repository::init(directory.path()).unwrap();
+
+        let base = commit(&repository, "Dev", 100, &[]);
+        let squash = commit(&repository, "GitHub", 400, &[base]);
+
+        assert_eq!(
+            classify(&repository, squash, Some(base)),
+            (MergeStrategy::Squash, None)
+        );
+        assert_eq!(
+            classify(&repository, squash, None),
+            (MergeStrategy::Squash, None)
+        );
+    }
+
+    #[test]
+    fn classify_rebase() {
+        let directory = TestDirectory::new();
+        let repository = Repository::init(directory.path()).unwrap();
+
+        // Rebased commits share the committer signature (including its time) of the last one:
+        let base = commit(&repository, "Dev", 100, &[]);
+        let first = commit(&repository, "GitHub", 400, &[base]);
+        let second = commit(&repository, "GitHub", 400, &[first]);
+        let last = commit(&repository, "GitHub", 400, &[second]);
+
+        assert_eq!(
+            classify(&repository, last, None),
+            (
+                MergeStrategy::Rebase,
+                Some(SkipRange {
+                    start: base,
+                    end: second
+                })
+            )
+        );
+
+        // A known base delimits the rebased commits, even if it shares their committer signature:
+        assert_eq!(
+            classify(&repository, last, Some(first)),
+            (
+                MergeStrategy::Rebase,
+                Some(SkipRange {
+                    start: first,
+                    end: second
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn classify_rebase_ends_at_other_committer() {
+        let directory = TestDirectory::new();
+        let repository = Repository::init(directory.path()).unwrap();
+
+        let base = commit(&repository, "Dev", 100, &[]);
+        let other = commit(&repository, "Other", 400, &[base]);
+        let first = commit(&repository, "GitHub", 400, &[other]);
+        let last = commit(&repository, "GitHub", 400, &[first]);
+
+        assert_eq!(
+            classify(&repository, last, None),
+            (
+                MergeStrategy::Rebase,
+                Some(SkipRange {
+                    start: other,
+                    end: first
+                })
+            )
+        );
+
+        // The same committer at a different time doesn't belong to the rebase either:
+        let earlier = commit(&repository, "GitHub", 300, &[base]);
+        let first = commit(&repository, "GitHub", 400, &[earlier]);
+        let last = commit(&repository, "GitHub", 400, &[first]);
+
+        assert_eq!(
+            classify(&repository, last, None),
+            (
+                MergeStrategy::Rebase,
+                Some(SkipRange {
+                    start: earlier,
+                    end: first
+                })
+            )
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::{fs, os::unix::process::ExitStatusExt, path::Path, process::ExitStatus};
+
+use futures::TryStreamExt;
+use git2::Repository as GitRepository;
+
+use crate::{
+    command::bisect::{pull_request_query, resolve_range, SkipPullRequestsConfig},
+    host::{GitHost, GitPullRequest},
+};
+
+/// Writes the host's merged pull requests that may overlap with the config's `good..bad` range
+/// to a JSON file at `path` (or to stdout, if `path` is `-`),
+/// to be passed via `--pull-requests-file` later on.
+///
+/// Pull requests are written unfiltered, so that the exported file
+/// reproduces the host's pull requests for any filters.
+pub async fn export_pull_requests(
+    host: &dyn GitHost,
+    config: &SkipPullRequestsConfig,
+    path: &Path,
+) -> anyhow::Result<ExitStatus> {
+    eprintln!("Opening git repository ...");
+    let repository = GitRepository::open(&config.directory)?;
+
+    let (good, bad) = resolve_range(&repository, config)?;
+    let query = pull_request_query(config, &repository, (&good, bad))?;
+
+    eprintln!("Requesting pull requests ...");
+    let pull_requests: Vec<GitPullRequest> = host
+        .merged_pull_requests(&config.repository, &query)
+        .try_collect()
+        .await?;
+
+    let json = serde_json::to_string_pretty(&pull_requests)?;
+
+    if path == Path::new("-") {
+        println!("{json}");
+    } else if config.dry_run {
+        eprintln!(
+            "Would write {count} pull requests to {path}",
+            count = pull_requests.len(),
+            path = path.display()
+        );
+    } else {
+        fs::write(path, json + "\n")?;
+        eprintln!(
+            "Wrote {count} pull requests to {path}",
+            count = pull_requests.len(),
+            path = path.display()
+        );
+    }
+
+    Ok(ExitStatus::from_raw(0))
+}
@@ -1,4 +1,11 @@
-use git2::{Commit as GitCommit, Error as GitError, Oid, Repository as GitRepository};
+mod bisect;
+
+pub(crate) use self::bisect::*;
+
+use git2::{
+    build::CheckoutBuilder, Commit as GitCommit, Error as GitError, ErrorCode as GitErrorCode, Oid,
+    Repository as GitRepository,
+};
 
 #[allow(dead_code)]
 pub(crate) fn commits_of_branch(
@@ -17,13 +24,15 @@ pub(crate) fn commits_of_branch(
         .collect()
 }
 
-pub(crate) fn commits_in_range(
-    repository: &GitRepository,
-    range: (Oid, Oid),
-) -> Result<Vec<GitCommit<'_>>, GitError> {
+pub(crate) fn commits_in_range<'r>(
+    repository: &'r GitRepository,
+    range: (&[Oid], Oid),
+) -> Result<Vec<GitCommit<'r>>, GitError> {
     let mut revwalk = repository.revwalk()?;
 
-    revwalk.hide(range.0)?;
+    for &good in range.0 {
+        revwalk.hide(good)?;
+    }
     revwalk.push(range.1)?;
 
     revwalk.simplify_first_parent()?;
@@ -33,3 +42,42 @@ pub(crate) fn commits_in_range(
         .map(|oid| repository.find_commit(oid))
         .collect()
 }
+
+pub(crate) fn commits_between(
+    repository: &GitRepository,
+    range: (Oid, Oid),
+) -> Result<Vec<Oid>, GitError> {
+    let mut revwalk = repository.revwalk()?;
+
+    revwalk.hide(range.0)?;
+    revwalk.push(range.1)?;
+
+    revwalk.collect()
+}
+
+/// Checks out the given commit as a detached `HEAD`,
+/// refusing to overwrite any local modifications.
+pub(crate) fn checkout_detached(
+    repository: &GitRepository,
+    commit_id: Oid,
+) -> Result<(), GitError> {
+    let commit = repository.find_commit(commit_id)?;
+
+    repository.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))?;
+    repository.set_head_detached(commit_id)?;
+
+    Ok(())
+}
+
+/// Returns the value of the git config entry (e.g. `assist.host.github.com.type`),
+/// as configured for the repository, or globally.
+pub(crate) fn config_string(
+    repository: &GitRepository,
+    name: &str,
+) -> Result<Option<String>, GitError> {
+    match repository.config()?.get_string(name) {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.code() == GitErrorCode::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    collections::BTreeSet,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use git2::{
+    Error as GitError, ErrorCode as GitErrorCode, Oid, Repository as GitRepository,
+    Signature as GitSignature,
+};
+
+const BISECT_START: &str = "BISECT_START";
+const BISECT_LOG: &str = "BISECT_LOG";
+const BISECT_TERMS: &str = "BISECT_TERMS";
+
+const DEFAULT_TERM_BAD: &str = "bad";
+const DEFAULT_TERM_GOOD: &str = "good";
+
+const REFLOG_MESSAGE: &str = "git-assist: bisect";
+
+/// The maximum number of refs written per transaction, as each holds an open lock file until it got committed.
+const MAX_REFS_PER_TRANSACTION: usize = 256;
+
+/// Returns `true` if a `git bisect` session is currently in progress.
+pub(crate) fn is_bisecting(repository: &GitRepository) -> bool {
+    git_dir_path(repository, BISECT_START).exists()
+}
+
+/// The commits marked within an in-progress `git bisect` session.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct BisectMarks {
+    /// The commits marked as "good" (i.e. `refs/bisect/good-*`).
+    pub(crate) good: Vec<Oid>,
+
+    /// The commit marked as "bad" (i.e. `refs/bisect/bad`).
+    pub(crate) bad: Option<Oid>,
+}
+
+/// Reads the good and bad marks of the in-progress `git bisect` session, if there is one.
+///
+/// Custom terms (i.e. `git bisect start --term-old=<term> --term-new=<term>`) are respected.
+pub(crate) fn bisect_marks(repository: &GitRepository) -> anyhow::Result<Option<BisectMarks>> {
+    if !is_bisecting(repository) {
+        return Ok(None);
+    }
+
+    let (term_bad, term_good) = bisect_terms(repository)?;
+
+    let bad = match repository.find_reference(&format!("refs/bisect/{term_bad}")) {
+        Ok(reference) => reference.target(),
+        Err(err) if err.code() == GitErrorCode::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let good = repository
+        .references_glob(&format!("refs/bisect/{term_good}-*"))?
+        .map(|reference| Ok(reference?.target()))
+        .filter_map(Result::transpose)
+        .collect::<Result<_, GitError>>()?;
+
+    Ok(Some(BisectMarks { good, bad }))
+}
+
+/// A mark of a commit within a `git bisect` session.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum BisectMark {
+    Good,
+    Bad,
+    Skip,
+}
+
+/// Marks the given commits as skipped, equivalent to `git bisect skip <commit>...`.
+pub(crate) fn skip_commits(
+    repository: &GitRepository,
+    commit_ids: impl IntoIterator<Item = Oid>,
+) -> anyhow::Result<()> {
+    mark_commits(repository, BisectMark::Skip, commit_ids)
+}
+
+/// Marks the given commits, equivalent to `git bisect <mark> <commit>...`.
+///
+/// The corresponding `refs/bisect/*` refs are written within transactions of up to `MAX_REFS_PER_TRANSACTION` refs,
+/// with their corresponding `BISECT_LOG` entries only appended once their transaction got committed.
+///
+/// As there can only be a single bad commit, marking multiple commits as bad is an error.
+pub(crate) fn mark_commits(
+    repository: &GitRepository,
+    mark: BisectMark,
+    commit_ids: impl IntoIterator<Item = Oid>,
+) -> anyhow::Result<()> {
+    if !is_bisecting(repository) {
+        anyhow::bail!("No bisect in progress, please run `git bisect start` first");
+    }
+
+    let (term_bad, term_good) = bisect_terms(repository)?;
+
+    let commit_ids: Vec<Oid> = commit_ids
+        .into_iter()
+        .collect::<BTreeSet<Oid>>()
+        .into_iter()
+        .collect();
+
+    if mark == BisectMark::Bad && commit_ids.len() > 1 {
+        anyhow::bail!("Only a single commit can be marked as {term_bad}");
+    }
+
+    let signature = match repository.signature() {
+        Ok(signature) => signature,
+        Err(_) => GitSignature::now("git-assist", "git-assist@localhost")?,
+    };
+
+    for batch in commit_ids.chunks(MAX_REFS_PER_TRANSACTION) {
+        let mut transaction = repository.transaction()?;
+        let mut log = String::new();
+
+        for &commit_id in batch {
+            let commit = repository.find_commit(commit_id)?;
+
+            let (term, refname) = match mark {
+                BisectMark::Good => (
+                    &term_good[..],
+                    format!("refs/bisect/{term_good}-{commit_id}"),
+                ),
+                BisectMark::Bad => (&term_bad[..], format!("refs/bisect/{term_bad}")),
+                BisectMark::Skip => ("skip", format!("refs/bisect/skip-{commit_id}")),
+            };
+
+            transaction.lock_ref(&refname)?;
+            transaction.set_target(&refname, commit_id, Some(&signature), REFLOG_MESSAGE)?;
+
+            log.push_str(&format!(
+                "# {term}: [{commit_id}] {summary}\ngit bisect {term} {commit_id}\n",
+                summary = commit.summary().unwrap_or_default()
+            ));
+        }
+
+        transaction.commit()?;
+
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(git_dir_path(repository, BISECT_LOG))?
+            .write_all(log.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Runs `git bisect <args>...` within the given directory, failing on a non-zero exit status.
+///
+/// In case of `dry_run` the command is merely printed.
+pub(crate) fn run_git_bisect(directory: &Path, args: &[&str], dry_run: bool) -> anyhow::Result<()> {
+    let formatted_args = args.join(" ");
+
+    if dry_run {
+        println!("git bisect {formatted_args}");
+        return Ok(());
+    }
+
+    let status = Command::new("git")
+        .arg("bisect")
+        .args(args)
+        .current_dir(directory)
+        .status()?;
+
+    if !status.success() {
+        anyhow::bail!("`git bisect {formatted_args}` failed with {status}");
+    }
+
+    Ok(())
+}
+
+/// Returns the `(bad, good)` terms of the in-progress `git bisect` session.
+fn bisect_terms(repository: &GitRepository) -> anyhow::Result<(String, String)> {
+    let path = git_dir_path(repository, BISECT_TERMS);
+
+    if !path.exists() {
+        return Ok((DEFAULT_TERM_BAD.to_owned(), DEFAULT_TERM_GOOD.to_owned()));
+    }
+
+    let contents = std::fs::read_to_string(path)?;
+    let mut lines = contents.lines().map(str::trim);
+
+    match (lines.next(), lines.next()) {
+        (Some(bad), Some(good)) => Ok((bad.to_owned(), good.to_owned())),
+        _ => anyhow::bail!("Malformed {BISECT_TERMS} file"),
+    }
+}
+
+fn git_dir_path(repository: &GitRepository, file_name: &str) -> PathBuf {
+    repository.path().join(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::TestDirectory;
+
+    use super::*;
+
+    #[test]
+    fn mark_commits_in_batches() {
+        let directory = TestDirectory::new();
+        let repository = GitRepository::init(directory.path()).unwrap();
+
+        let signature = GitSignature::now("Test", "test@example.com").unwrap();
+        let tree = repository
+            .find_tree(repository.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+
+        let mut commit_ids: Vec<Oid> = vec![];
+
+        for index in 0..(2 * MAX_REFS_PER_TRANSACTION + 1) {
+            let parents: Vec<_> = commit_ids
+                .last()
+                .map(|&parent| repository.find_commit(parent).unwrap())
+                .into_iter()
+                .collect();
+            let parents: Vec<_> = parents.iter().collect();
+
+            let message = format!("Commit {index}");
+            commit_ids.push(
+                repository
+                    .commit(None, &signature, &signature, &message, &tree, &parents)
+                    .unwrap(),
+            );
+        }
+
+        std::fs::write(git_dir_path(&repository, BISECT_START), "main\n").unwrap();
+
+        skip_commits(&repository, commit_ids.iter().copied()).unwrap();
+
+        let skipped = repository
+            .references_glob("refs/bisect/skip-*")
+            .unwrap()
+            .count();
+        assert_eq!(skipped, commit_ids.len());
+
+        let log = std::fs::read_to_string(git_dir_path(&repository, BISECT_LOG)).unwrap();
+        let logged = log
+            .lines()
+            .filter(|line| line.starts_with("git bisect skip "))
+            .count();
+        assert_eq!(logged, commit_ids.len());
+    }
+}
@@ -1,17 +1,125 @@
 #[cfg(feature = "github")]
 mod github;
 
+#[cfg(feature = "gitlab")]
+mod gitlab;
+
+#[cfg(feature = "gitea")]
+mod gitea;
+
+#[cfg(feature = "bitbucket")]
+mod bitbucket;
+
+#[cfg(feature = "azure")]
+mod azure;
+
+#[cfg_attr(not(any(feature = "github", feature = "reqwest")), allow(dead_code))]
+mod auth;
+mod cache;
+#[cfg_attr(not(any(feature = "github", feature = "reqwest")), allow(dead_code))]
+mod credentials;
+mod file;
+mod filter;
+mod messages;
+mod refs;
+
 use std::str::FromStr;
 
+pub use self::{auth::*, cache::*, file::*, filter::*, messages::*, refs::*};
+
+#[cfg(feature = "github")]
 pub use self::github::*;
 
-use async_trait::async_trait;
+#[cfg(feature = "gitlab")]
+pub use self::gitlab::*;
+
+#[cfg(feature = "gitea")]
+pub use self::gitea::*;
+
+#[cfg(feature = "bitbucket")]
+pub use self::bitbucket::*;
+
+#[cfg(feature = "azure")]
+pub use self::azure::*;
+
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
 use git_url_parse::GitUrl;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use url::Url;
 
 pub const GITHUB_HOST: &str = "github.com";
+pub const GITLAB_HOST: &str = "gitlab.com";
+pub const BITBUCKET_HOST: &str = "bitbucket.org";
+
+pub const AZURE_DEVOPS_HOST: &str = "dev.azure.com";
+pub const AZURE_DEVOPS_SSH_HOST: &str = "ssh.dev.azure.com";
+
+/// The host suffix of legacy Azure DevOps (formerly Visual Studio Team Services) organizations.
+pub const VISUAL_STUDIO_HOST_SUFFIX: &str = ".visualstudio.com";
+pub const VISUAL_STUDIO_SSH_HOST: &str = "vs-ssh.visualstudio.com";
 
+/// The default port of Bitbucket Data Center's ssh server.
+const BITBUCKET_DATA_CENTER_SSH_PORT: u16 = 7999;
+
+/// The kinds of hosts supported, named after their respective features.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SupportedHost {
     Github,
+    Gitlab,
+    Gitea,
+    Bitbucket,
+    AzureDevOps,
+}
+
+impl SupportedHost {
+    /// Detects the url's host, probing it for hosts that can't be recognized by name alone.
+    ///
+    /// Gitea/Forgejo instances have arbitrary hostnames, which is why they are only
+    /// recognized by name if hosted on a `gitea.`/`forgejo.` subdomain, and probed otherwise.
+    pub async fn detect(url: &GitUrl) -> anyhow::Result<Self> {
+        let err = match Self::try_from(url) {
+            Ok(host) => return Ok(host),
+            Err(err) => err,
+        };
+
+        #[cfg(feature = "gitea")]
+        if url.host().is_some() && self::gitea::is_gitea_host(url).await {
+            return Ok(SupportedHost::Gitea);
+        }
+
+        Err(err)
+    }
+}
+
+impl FromStr for SupportedHost {
+    type Err = anyhow::Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str.to_lowercase().as_str() {
+            "github" => Ok(Self::Github),
+            "gitlab" => Ok(Self::Gitlab),
+            "gitea" | "forgejo" => Ok(Self::Gitea),
+            "bitbucket" => Ok(Self::Bitbucket),
+            "azure" => Ok(Self::AzureDevOps),
+            _ => Err(anyhow::anyhow!(
+                "Unknown host type {str:?}, expected one of: github, gitlab, gitea, bitbucket, azure"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SupportedHost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Github => write!(f, "github"),
+            Self::Gitlab => write!(f, "gitlab"),
+            Self::Gitea => write!(f, "gitea"),
+            Self::Bitbucket => write!(f, "bitbucket"),
+            Self::AzureDevOps => write!(f, "azure"),
+        }
+    }
 }
 
 impl TryFrom<&GitUrl> for SupportedHost {
@@ -26,26 +134,137 @@ impl TryFrom<&GitUrl> for SupportedHost {
                     Err(anyhow::anyhow!("Github support is only available when compiled with `--features \"github\"`"))
                 }
             }
+            // Self-managed Gitlab instances are commonly hosted on a `gitlab.` subdomain:
+            Some(host) if host == GITLAB_HOST || host.starts_with("gitlab.") => {
+                if cfg!(feature = "gitlab") {
+                    Ok(SupportedHost::Gitlab)
+                } else {
+                    Err(anyhow::anyhow!("Gitlab support is only available when compiled with `--features \"gitlab\"`"))
+                }
+            }
+            Some(host) if host.starts_with("gitea.") || host.starts_with("forgejo.") => {
+                if cfg!(feature = "gitea") {
+                    Ok(SupportedHost::Gitea)
+                } else {
+                    Err(anyhow::anyhow!(
+                        "Gitea support is only available when compiled with `--features \"gitea\"`"
+                    ))
+                }
+            }
+            // Azure DevOps Server instances are recognized by their `_git` url scheme:
+            Some(host)
+                if host == AZURE_DEVOPS_HOST
+                    || host == AZURE_DEVOPS_SSH_HOST
+                    || host.ends_with(VISUAL_STUDIO_HOST_SUFFIX)
+                    || path_segments(url).contains(&"_git") =>
+            {
+                if cfg!(feature = "azure") {
+                    Ok(SupportedHost::AzureDevOps)
+                } else {
+                    Err(anyhow::anyhow!("Azure DevOps support is only available when compiled with `--features \"azure\"`"))
+                }
+            }
+            // Bitbucket Data Center instances are recognized by their distinct url schemes:
+            Some(host)
+                if host == BITBUCKET_HOST
+                    || host.starts_with("bitbucket.")
+                    || url.port() == Some(BITBUCKET_DATA_CENTER_SSH_PORT)
+                    || is_bitbucket_data_center_path(&path_segments(url)) =>
+            {
+                if cfg!(feature = "bitbucket") {
+                    Ok(SupportedHost::Bitbucket)
+                } else {
+                    Err(anyhow::anyhow!("Bitbucket support is only available when compiled with `--features \"bitbucket\"`"))
+                }
+            }
             Some(host) => Err(anyhow::anyhow!("Unsupported host: {host:?}")),
             None => Err(anyhow::anyhow!("Unable to detect host: {url}")),
         }
     }
 }
 
-#[async_trait]
+/// Returns `true` if the path segments match any of the Bitbucket Data Center url schemes
+/// (i.e. ending with `scm/{project}/{slug}`, or containing `projects/{project}/repos/{slug}`).
+fn is_bitbucket_data_center_path(segments: &[&str]) -> bool {
+    matches!(segments, [.., "scm", _, _])
+        || segments
+            .windows(4)
+            .any(|window| matches!(window, ["projects" | "users", _, "repos", _]))
+}
+
+/// A stream of pull requests, as requested from a host.
+pub type PullRequestStream<'a> = BoxStream<'a, Result<GitPullRequest, anyhow::Error>>;
+
 pub trait GitHost {
-    async fn merged_pull_requests(
-        &self,
-        repository: &GitRepositoryUrl,
-    ) -> Result<Vec<GitPullRequest>, anyhow::Error>;
+    /// Returns a stream of the repository's merged pull requests matching the query.
+    ///
+    /// Pull requests are streamed as they arrive (e.g. page by page)
+    /// and implementations may end the stream early once past the query's time window.
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a>;
+}
+
+/// Criteria for narrowing down the pull requests requested from a host.
+#[derive(Clone, Default, Debug)]
+pub struct PullRequestQuery {
+    /// The branch the pull requests got merged into.
+    pub base_ref: Option<String>,
+
+    /// The earliest point in time the pull requests got merged at.
+    pub merged_after: Option<DateTime<Utc>>,
+
+    /// The latest point in time the pull requests got merged at.
+    pub merged_before: Option<DateTime<Utc>>,
 }
 
+impl PullRequestQuery {
+    /// Returns `true` if the merge time lies within the query's time window.
+    pub fn contains_merged_at(&self, merged_at: DateTime<Utc>) -> bool {
+        let is_after = self.merged_after.is_none_or(|after| merged_at >= after);
+        let is_before = self.merged_before.is_none_or(|before| merged_at <= before);
+
+        is_after && is_before
+    }
+
+    /// Returns `true` if the pull request got merged into the query's base branch within its time window.
+    ///
+    /// Pull requests without a merge time can't be ruled out by the latter.
+    pub fn matches(&self, pull_request: &GitPullRequest) -> bool {
+        let is_on_base = self
+            .base_ref
+            .as_ref()
+            .is_none_or(|base_ref| &pull_request.base_ref == base_ref);
+
+        let is_merged = pull_request
+            .merged_at
+            .is_none_or(|merged_at| self.contains_merged_at(merged_at));
+
+        is_on_base && is_merged
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GitPullRequest {
     pub identifier: String,
     pub title: Option<String>,
+    pub author: Option<String>,
+    pub labels: Vec<String>,
+
+    pub base_ref: String,
+
+    /// The base branch's commit the pull request is based on, if reported by the host
+    /// (defaulting to the merge commit's first parent).
+    #[serde(default)]
+    pub base_sha: Option<String>,
 
-    pub base_sha: String,
     pub merge_sha: String,
+
+    /// The point in time the pull request got merged at, if reported by the host.
+    #[serde(default)]
+    pub merged_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Debug)]
@@ -54,6 +273,133 @@ pub struct GitRepositoryUrl {
     pub parsed_url: GitUrl,
 }
 
+/// The coordinates of a repository on its host.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RepositoryCoordinates {
+    /// The host (e.g. `github.com`).
+    pub host: String,
+
+    /// The namespace containing the repository, from outermost to innermost
+    /// (e.g. `[owner]` on Github, `[group, subgroup]` on Gitlab,
+    /// or `[organization, project]` on Azure DevOps).
+    pub namespace: Vec<String>,
+
+    /// The repository's name.
+    pub name: String,
+}
+
+impl GitRepositoryUrl {
+    /// Returns the segments of the url's path, without any `.git` suffix.
+    pub fn path_segments(&self) -> Vec<&str> {
+        path_segments(&self.parsed_url)
+    }
+
+    /// Returns the url of the repository's host (e.g. `https://github.com/`),
+    /// keeping the scheme and port of http(s) urls, but defaulting to `https` for any other ones.
+    pub fn host_url(&self) -> anyhow::Result<Url> {
+        let Some(host) = self.parsed_url.host() else {
+            anyhow::bail!("No host found in url: {url}", url = self.url_string);
+        };
+
+        let (scheme, port) = match self.parsed_url.scheme() {
+            Some(scheme @ ("http" | "https")) => (scheme, self.parsed_url.port()),
+            _ => ("https", None),
+        };
+
+        let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+        Ok(Url::parse(&format!("{scheme}://{host}{port}/"))?)
+    }
+
+    /// Returns the repository's coordinates on its host.
+    ///
+    /// Besides the common `{namespace}/{name}` url scheme, this supports Azure DevOps'
+    /// `{organization}/{project}/_git/{name}` and `v3/{organization}/{project}/{name}` url schemes.
+    pub fn coordinates(&self) -> anyhow::Result<RepositoryCoordinates> {
+        let url = &self.url_string;
+
+        let Some(host) = self.parsed_url.host() else {
+            anyhow::bail!("No host found in url: {url}");
+        };
+
+        let segments = self.decoded_path_segments();
+
+        let (mut namespace, name) = match segments.iter().position(|segment| segment == "_git") {
+            Some(index) => match &segments[(index + 1)..] {
+                [name] => (segments[..index].to_vec(), name.clone()),
+                _ => anyhow::bail!("Not a repository url: {url}"),
+            },
+            None => match segments.split_last() {
+                Some((name, namespace)) => (namespace.to_vec(), name.clone()),
+                None => anyhow::bail!("Not a repository url: {url}"),
+            },
+        };
+
+        if host == AZURE_DEVOPS_SSH_HOST || host == VISUAL_STUDIO_SSH_HOST {
+            if namespace.first().is_some_and(|segment| segment == "v3") {
+                namespace.remove(0);
+            }
+        } else if let Some(organization) = host.strip_suffix(VISUAL_STUDIO_HOST_SUFFIX) {
+            if namespace
+                .first()
+                .is_some_and(|segment| segment == "DefaultCollection")
+            {
+                namespace.remove(0);
+            }
+            namespace.insert(0, organization.to_owned());
+        }
+
+        if namespace.is_empty() {
+            anyhow::bail!("Not a repository url: {url}");
+        }
+
+        Ok(RepositoryCoordinates {
+            host: host.to_owned(),
+            namespace,
+            name,
+        })
+    }
+
+    /// Returns the percent-decoded segments of the url's path, without any `.git` suffix.
+    fn decoded_path_segments(&self) -> Vec<String> {
+        // `GitUrl` truncates paths at percent-encoded characters, which is why
+        // urls get re-parsed, unless they use the scp-like syntax (e.g. `git@host:path`):
+        let Some(segments) = Url::parse(&self.url_string)
+            .ok()
+            .and_then(|url| Some(url.path_segments()?.map(str::to_owned).collect::<Vec<_>>()))
+        else {
+            return self
+                .path_segments()
+                .into_iter()
+                .map(str::to_owned)
+                .collect();
+        };
+
+        let mut segments: Vec<String> = segments
+            .iter()
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+            .collect();
+
+        if let Some(last) = segments.last_mut() {
+            if let Some(stripped) = last.strip_suffix(".git") {
+                *last = stripped.to_owned();
+            }
+        }
+
+        segments
+    }
+}
+
+fn path_segments(url: &GitUrl) -> Vec<&str> {
+    let path = url.path().trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
 impl FromStr for GitRepositoryUrl {
     type Err = anyhow::Error;
 
@@ -67,3 +413,29 @@ impl FromStr for GitRepositoryUrl {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_bitbucket_data_center_path_only_matches_scm_before_project_and_slug() {
+        assert!(is_bitbucket_data_center_path(&["scm", "PROJ", "repo"]));
+        assert!(is_bitbucket_data_center_path(&[
+            "bitbucket",
+            "scm",
+            "PROJ",
+            "repo"
+        ]));
+        assert!(is_bitbucket_data_center_path(&[
+            "projects", "PROJ", "repos", "repo", "browse"
+        ]));
+
+        // Groups or repositories that merely happen to be named `scm`:
+        assert!(!is_bitbucket_data_center_path(&[
+            "scm", "group", "subgroup", "repo"
+        ]));
+        assert!(!is_bitbucket_data_center_path(&["group", "scm"]));
+        assert!(!is_bitbucket_data_center_path(&["group", "scm", "repo"]));
+    }
+}
@@ -0,0 +1,266 @@
+use std::{fmt, fs, io::IsTerminal, path::PathBuf, str::FromStr};
+
+use inquire::Confirm;
+use secrecy::SecretString;
+use url::Url;
+
+use super::credentials::{has_credential_helper, GitCredential};
+
+/// A method of authenticating with a host.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum AuthMethod {
+    /// No authentication (public repositories only, with lower rate limits).
+    None,
+
+    /// An access token.
+    Token,
+
+    /// A Github App.
+    App,
+}
+
+impl FromStr for AuthMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        match str {
+            "none" => Ok(Self::None),
+            "token" => Ok(Self::Token),
+            "app" => Ok(Self::App),
+            _ => anyhow::bail!(
+                "Unsupported authentication method {str:?}, expected `none`, `token`, or `app`"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for AuthMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::Token => write!(f, "token"),
+            Self::App => write!(f, "app"),
+        }
+    }
+}
+
+/// The username that tokens get stored under via the git credential helpers,
+/// for hosts that accept tokens regardless of the username.
+#[cfg_attr(
+    not(any(
+        feature = "github",
+        feature = "gitlab",
+        feature = "gitea",
+        feature = "azure"
+    )),
+    allow(dead_code)
+)]
+pub(crate) const TOKEN_USERNAME: &str = "token";
+
+/// An access token, along with the git credentials it got resolved from (or is to be stored as), if any.
+#[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+#[derive(Debug)]
+pub(crate) struct Token {
+    pub(crate) secret: SecretString,
+    pub(crate) credential: Option<GitCredential>,
+}
+
+#[cfg_attr(
+    not(any(feature = "gitlab", feature = "gitea", feature = "azure")),
+    allow(dead_code)
+)]
+impl Token {
+    /// Splits a token (if any) into its secret and git credentials.
+    pub(crate) fn split(token: Option<Self>) -> (Option<SecretString>, Option<GitCredential>) {
+        match token {
+            Some(Self { secret, credential }) => (Some(secret), credential),
+            None => (None, None),
+        }
+    }
+}
+
+impl From<SecretString> for Token {
+    fn from(secret: SecretString) -> Self {
+        Self {
+            secret,
+            credential: None,
+        }
+    }
+}
+
+/// Options for resolving the credentials to authenticate with a host.
+///
+/// Credentials are resolved from (in order) the token file, the host's environment variables,
+/// the git credential helpers, and finally prompts, unless running non-interactively.
+#[derive(Clone, Default, Debug)]
+pub struct AuthOptions {
+    /// The method to authenticate with, unless to be picked from the available credentials.
+    pub method: Option<AuthMethod>,
+
+    /// A file to read the access token from.
+    pub token_file: Option<PathBuf>,
+
+    /// Fail instead of prompting for credentials that can't be resolved otherwise.
+    pub non_interactive: bool,
+
+    /// The ID of the Github App to authenticate as.
+    pub app_id: Option<u64>,
+
+    /// The PEM file containing the Github App's private key.
+    pub app_key_file: Option<PathBuf>,
+
+    /// The ID of the Github App's installation to authenticate as, unless to be found for the repository.
+    pub installation_id: Option<u64>,
+}
+
+impl AuthOptions {
+    /// Returns the method to authenticate with, which is implied to be `App`
+    /// if any of the Github App's options are set.
+    pub fn method(&self) -> Option<AuthMethod> {
+        let is_app =
+            self.app_id.is_some() || self.app_key_file.is_some() || self.installation_id.is_some();
+
+        self.method.or(is_app.then_some(AuthMethod::App))
+    }
+
+    /// Returns `true` if prompting for credentials is allowed
+    /// (i.e. unless running non-interactively, or without a terminal to prompt on).
+    pub fn is_interactive(&self) -> bool {
+        !self.non_interactive && std::io::stdin().is_terminal()
+    }
+
+    /// Resolves an access token from the token file, or from the first of the environment variables that is set,
+    /// without prompting for it.
+    pub(crate) fn token(&self, env_vars: &[&str]) -> anyhow::Result<Option<SecretString>> {
+        if let Some(path) = &self.token_file {
+            let token = fs::read_to_string(path).map_err(|err| {
+                anyhow::anyhow!(
+                    "Could not read token file {path}: {err}",
+                    path = path.display()
+                )
+            })?;
+
+            let token = token.trim();
+
+            if token.is_empty() {
+                anyhow::bail!("Token file {path} is empty", path = path.display());
+            }
+
+            eprintln!("Using token from {path}", path = path.display());
+            return Ok(Some(SecretString::from(token)));
+        }
+
+        for env_var in env_vars {
+            if let Ok(token) = std::env::var(env_var) {
+                if !token.is_empty() {
+                    eprintln!("Using {env_var} from environment");
+                    return Ok(Some(SecretString::from(token)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Asks the git credential helpers for credentials for the host of `url`, without prompting for them.
+    pub(crate) fn stored_credential(&self, url: &Url) -> anyhow::Result<Option<GitCredential>> {
+        let Some(credential) = GitCredential::fill(url)? else {
+            return Ok(None);
+        };
+
+        eprintln!(
+            "Using credentials of git credential helper for {host}",
+            host = credential.host()
+        );
+
+        Ok(Some(credential))
+    }
+
+    /// Offers to store a token that got prompted for via the git credential helpers (if any are configured),
+    /// returning the credentials to be approved once the host accepted them.
+    pub(crate) fn offer_to_store(
+        &self,
+        url: &Url,
+        username: &str,
+        token: &SecretString,
+    ) -> anyhow::Result<Option<GitCredential>> {
+        if !self.is_interactive() || !has_credential_helper(url) {
+            return Ok(None);
+        }
+
+        let store = Confirm::new("Store token via git credential helper?")
+            .with_default(true)
+            .prompt()?;
+
+        if !store {
+            return Ok(None);
+        }
+
+        Ok(Some(GitCredential::new(url, username, token.clone())?))
+    }
+
+    /// Fails with an explanation of how to pass credentials, unless prompting for them is allowed.
+    pub(crate) fn ensure_interactive(&self, env_vars: &[&str]) -> anyhow::Result<()> {
+        if self.is_interactive() {
+            return Ok(());
+        }
+
+        let env_vars = env_vars
+            .iter()
+            .map(|env_var| format!("`{env_var}`"))
+            .collect::<Vec<_>>()
+            .join(" or ");
+
+        let reason = if self.non_interactive {
+            "running non-interactively"
+        } else {
+            "stdin is not a terminal"
+        };
+
+        anyhow::bail!(
+            "No credentials found and {reason}: set {env_vars}, pass `--token-file <PATH>`, or pass `--auth none`"
+        )
+    }
+
+    /// Resolves an access token via `token`, or via `stored_credential` for the host of `url`,
+    /// or prompts for one (if allowed), for hosts that only support token authentication.
+    ///
+    /// Tokens that got prompted for may get stored under the given `username`.
+    #[cfg_attr(not(feature = "reqwest"), allow(dead_code))]
+    pub(crate) fn token_or_prompt(
+        &self,
+        env_vars: &[&str],
+        url: &Url,
+        username: &str,
+        prompt: impl FnOnce() -> anyhow::Result<Option<SecretString>>,
+    ) -> anyhow::Result<Option<Token>> {
+        match self.method() {
+            Some(AuthMethod::None) => return Ok(None),
+            Some(AuthMethod::App) => {
+                anyhow::bail!("App authentication is only supported for Github hosts")
+            }
+            Some(AuthMethod::Token) | None => {}
+        }
+
+        if let Some(token) = self.token(env_vars)? {
+            return Ok(Some(Token::from(token)));
+        }
+
+        if let Some(credential) = self.stored_credential(url)? {
+            return Ok(Some(Token {
+                secret: credential.password().clone(),
+                credential: Some(credential),
+            }));
+        }
+
+        self.ensure_interactive(env_vars)?;
+
+        let Some(secret) = prompt()? else {
+            return Ok(None);
+        };
+
+        let credential = self.offer_to_store(url, username, &secret)?;
+
+        Ok(Some(Token { secret, credential }))
+    }
+}
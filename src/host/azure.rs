@@ -0,0 +1,348 @@
+use chrono::{DateTime, Utc};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use inquire::{Password, PasswordDisplayMode};
+use reqwest::{Client, RequestBuilder, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::host::{
+    credentials::{send_request, GitCredential},
+    AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream,
+    RepositoryCoordinates, Token, AZURE_DEVOPS_HOST, AZURE_DEVOPS_SSH_HOST, TOKEN_USERNAME,
+    VISUAL_STUDIO_HOST_SUFFIX, VISUAL_STUDIO_SSH_HOST,
+};
+
+/// The environment variable to read an Azure DevOps personal access token from
+/// (shared with the Azure DevOps extension of the Azure CLI).
+const AZURE_DEVOPS_TOKEN_VAR: &str = "AZURE_DEVOPS_EXT_PAT";
+
+const API_VERSION: &str = "7.1";
+
+const PER_PAGE: usize = 100;
+
+#[derive(Debug)]
+pub struct AzureDevOpsApi {
+    client: Client,
+    collection_url: Url,
+    token: Option<SecretString>,
+    credential: Option<GitCredential>,
+}
+
+impl AzureDevOpsApi {
+    /// Creates a client for the Azure DevOps REST API of the organization (or collection)
+    /// at `collection_url` (e.g. `https://dev.azure.com/{organization}/`).
+    pub fn new(collection_url: Url, token: Option<SecretString>) -> Self {
+        Self {
+            client: Client::new(),
+            collection_url,
+            token,
+            credential: None,
+        }
+    }
+
+    /// Creates a client for the Azure DevOps organization (or collection) hosting the repository
+    /// (or at `collection_url`, if provided),
+    /// authenticated via `AZURE_DEVOPS_EXT_PAT` (or the token file),
+    /// a git credential helper, or a token prompted for, as per `auth`.
+    pub fn authenticated(
+        repository: &GitRepositoryUrl,
+        collection_url: Option<Url>,
+        auth: &AuthOptions,
+    ) -> anyhow::Result<Self> {
+        let collection_url = match collection_url {
+            Some(collection_url) => collection_url,
+            None => AzureRepository::try_from(repository.clone())?.collection_url()?,
+        };
+
+        let credential_url = collection_url.clone();
+        let (token, credential) = Token::split(pick_token(&credential_url, auth)?);
+
+        Ok(Self {
+            credential,
+            ..Self::new(collection_url, token)
+        })
+    }
+
+    fn get(&self, url: Url) -> RequestBuilder {
+        let request = self.client.get(url);
+
+        // Personal access tokens are passed as password, with an empty username:
+        match &self.token {
+            Some(token) => request.basic_auth("", Some(token.expose_secret())),
+            None => request,
+        }
+    }
+
+    fn pull_requests_url(&self, repository: &AzureRepository) -> anyhow::Result<Url> {
+        let mut url = self.collection_url.clone();
+
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Azure DevOps url: {}", self.collection_url))?
+            .pop_if_empty()
+            .extend([
+                repository.project(),
+                "_apis",
+                "git",
+                "repositories",
+                repository.name(),
+                "pullrequests",
+            ]);
+
+        Ok(url)
+    }
+
+    async fn pull_requests_page(
+        &self,
+        pull_requests_url: &Url,
+        query: &PullRequestQuery,
+        skip: usize,
+    ) -> anyhow::Result<Vec<PullRequest>> {
+        let mut request = self.get(pull_requests_url.clone()).query(&[
+            ("searchCriteria.status", "completed"),
+            ("$top", &PER_PAGE.to_string()),
+            ("$skip", &skip.to_string()),
+            ("api-version", API_VERSION),
+        ]);
+
+        if let Some(base_ref) = &query.base_ref {
+            request = request.query(&[(
+                "searchCriteria.targetRefName",
+                format!("refs/heads/{base_ref}"),
+            )]);
+        }
+
+        if query.merged_after.is_some() || query.merged_before.is_some() {
+            request = request.query(&[("searchCriteria.queryTimeRangeType", "closed")]);
+        }
+
+        if let Some(merged_after) = query.merged_after {
+            request = request.query(&[("searchCriteria.minTime", merged_after.to_rfc3339())]);
+        }
+
+        if let Some(merged_before) = query.merged_before {
+            request = request.query(&[("searchCriteria.maxTime", merged_before.to_rfc3339())]);
+        }
+
+        let page: PullRequestsPage = send_request(request, self.credential.as_ref())
+            .await?
+            .json()
+            .await?;
+
+        Ok(page.value)
+    }
+}
+
+/// The coordinates of a repository hosted on Azure DevOps (Services or Server).
+#[derive(Clone, Debug)]
+pub struct AzureRepository {
+    coordinates: RepositoryCoordinates,
+    url: GitRepositoryUrl,
+}
+
+impl TryFrom<GitRepositoryUrl> for AzureRepository {
+    type Error = anyhow::Error;
+
+    fn try_from(repository: GitRepositoryUrl) -> Result<Self, anyhow::Error> {
+        let mut coordinates = repository.coordinates()?;
+
+        // Repositories named after their project may omit the latter (e.g. `{organization}/_git/{name}`):
+        if coordinates.namespace.len() == 1 {
+            coordinates.namespace.push(coordinates.name.clone());
+        }
+
+        Ok(Self {
+            coordinates,
+            url: repository,
+        })
+    }
+}
+
+impl AzureRepository {
+    /// Returns the repository's organization (or collection, on Azure DevOps Server).
+    pub fn organization(&self) -> &str {
+        let namespace = &self.coordinates.namespace;
+        &namespace[namespace.len() - 2]
+    }
+
+    /// Returns the repository's project.
+    pub fn project(&self) -> &str {
+        let namespace = &self.coordinates.namespace;
+        &namespace[namespace.len() - 1]
+    }
+
+    /// Returns the repository's name.
+    pub fn name(&self) -> &str {
+        &self.coordinates.name
+    }
+
+    /// Returns the url of the organization (or collection) hosting the repository.
+    pub fn collection_url(&self) -> anyhow::Result<Url> {
+        let host = self.coordinates.host.as_str();
+
+        if host == AZURE_DEVOPS_HOST
+            || host == AZURE_DEVOPS_SSH_HOST
+            || host == VISUAL_STUDIO_SSH_HOST
+        {
+            let mut url = Url::parse(&format!("https://{AZURE_DEVOPS_HOST}/"))?;
+            url.path_segments_mut()
+                .map_err(|_| anyhow::anyhow!("Invalid Azure DevOps host: {host}"))?
+                .pop_if_empty()
+                .extend([self.organization(), ""]);
+            return Ok(url);
+        }
+
+        if host.ends_with(VISUAL_STUDIO_HOST_SUFFIX) {
+            return Ok(Url::parse(&format!("https://{host}/"))?);
+        }
+
+        // Azure DevOps Server collections may live at any path (e.g. `/tfs/{collection}`):
+        let (scheme, port) = match self.url.parsed_url.scheme() {
+            Some(scheme @ ("http" | "https")) => (scheme, self.url.parsed_url.port()),
+            _ => ("https", None),
+        };
+
+        let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+        let namespace = &self.coordinates.namespace;
+
+        let mut url = Url::parse(&format!("{scheme}://{host}{port}/"))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Azure DevOps host: {host}"))?
+            .pop_if_empty()
+            .extend(&namespace[..(namespace.len() - 1)])
+            .push("");
+
+        Ok(url)
+    }
+}
+
+impl GitHost for AzureDevOpsApi {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let pull_requests_url = AzureRepository::try_from(repository.clone())
+            .and_then(|safe_repository| self.pull_requests_url(&safe_repository));
+
+        let pull_requests_url = match pull_requests_url {
+            Ok(pull_requests_url) => pull_requests_url,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        let pages = stream::try_unfold(Some(0), move |skip| {
+            let pull_requests_url = pull_requests_url.clone();
+            async move {
+                let Some(skip) = skip else {
+                    return Ok::<_, anyhow::Error>(None);
+                };
+
+                let pull_requests = self
+                    .pull_requests_page(&pull_requests_url, query, skip)
+                    .await?;
+
+                // Azure DevOps doesn't report the next page, so a partial page marks the last one:
+                let next_skip = (pull_requests.len() == PER_PAGE).then_some(skip + PER_PAGE);
+
+                Ok(Some((pull_requests, next_skip)))
+            }
+        });
+
+        pages
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            .try_filter(move |pull_request| {
+                let is_merged = pull_request
+                    .closed_date
+                    .is_some_and(|closed_date| query.contains_merged_at(closed_date));
+
+                future::ready(is_merged)
+            })
+            .and_then(|pull_request| future::ready(git_pull_request(pull_request)))
+            .boxed()
+    }
+}
+
+fn git_pull_request(pull_request: PullRequest) -> anyhow::Result<GitPullRequest> {
+    let Some(merge_commit) = pull_request.last_merge_commit else {
+        anyhow::bail!("Could not find merge commit sha");
+    };
+
+    let Some(base_commit) = pull_request.last_merge_target_commit else {
+        anyhow::bail!(
+            "Could not find base sha of !{}",
+            pull_request.pull_request_id
+        );
+    };
+
+    let base_ref = match pull_request.target_ref_name.strip_prefix("refs/heads/") {
+        Some(base_ref) => base_ref.to_owned(),
+        None => pull_request.target_ref_name,
+    };
+
+    Ok(GitPullRequest {
+        identifier: pull_request.pull_request_id.to_string(),
+        title: pull_request.title,
+        author: pull_request.created_by.map(|author| author.unique_name),
+        labels: pull_request
+            .labels
+            .into_iter()
+            .map(|label| label.name)
+            .collect(),
+        base_ref,
+        base_sha: Some(base_commit.commit_id),
+        merge_sha: merge_commit.commit_id,
+        merged_at: pull_request.closed_date,
+    })
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct PullRequestsPage {
+    value: Vec<PullRequest>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequest {
+    pull_request_id: u64,
+    title: Option<String>,
+    created_by: Option<Identity>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    target_ref_name: String,
+    last_merge_target_commit: Option<Commit>,
+    last_merge_commit: Option<Commit>,
+    closed_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Identity {
+    unique_name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Commit {
+    commit_id: String,
+}
+
+fn pick_token(url: &Url, auth: &AuthOptions) -> anyhow::Result<Option<Token>> {
+    auth.token_or_prompt(&[AZURE_DEVOPS_TOKEN_VAR], url, TOKEN_USERNAME, || {
+        let token = Password::new("Personal access token (leave empty for no authentication):")
+            .with_display_toggle_enabled()
+            .with_display_mode(PasswordDisplayMode::Hidden)
+            .prompt()?;
+
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SecretString::from(token)))
+    })
+}
@@ -0,0 +1,783 @@
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use inquire::{Password, PasswordDisplayMode};
+use reqwest::{Client, RequestBuilder, StatusCode, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::host::{
+    credentials::{send_request, GitCredential},
+    AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream,
+    Token, BITBUCKET_HOST,
+};
+
+/// The environment variable to read a Bitbucket access token (or app password) from.
+const BITBUCKET_TOKEN_VAR: &str = "BITBUCKET_TOKEN";
+
+/// The environment variable to read a Bitbucket username from, for basic authentication.
+const BITBUCKET_USERNAME_VAR: &str = "BITBUCKET_USERNAME";
+
+/// The username that git authenticates with access tokens under.
+const ACCESS_TOKEN_USERNAME: &str = "x-token-auth";
+
+/// The url of the Bitbucket Cloud REST API.
+const BITBUCKET_CLOUD_API_URL: &str = "https://api.bitbucket.org/2.0/";
+
+const CLOUD_PER_PAGE: &str = "50";
+const DATA_CENTER_PER_PAGE: &str = "100";
+
+/// The order of Bitbucket Data Center's pull requests by when they got closed (most recently first),
+/// which older servers don't support.
+const DATA_CENTER_ORDER_CLOSED: &str = "CLOSED_DATE";
+
+/// The order of Bitbucket Data Center's pull requests by when they got created (most recently first).
+const DATA_CENTER_ORDER_NEWEST: &str = "NEWEST";
+
+/// Credentials for authenticating with Bitbucket.
+pub enum BitbucketCredentials {
+    /// An access token (e.g. a repository/project/workspace access token, or a personal access token).
+    Token(SecretString),
+
+    /// A username, together with an app password or an API token.
+    Basic {
+        username: String,
+        password: SecretString,
+    },
+}
+
+impl std::fmt::Debug for BitbucketCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Token(_) => f.debug_tuple("Token").finish_non_exhaustive(),
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct BitbucketApi {
+    client: Client,
+    api_url: Url,
+    credentials: Option<BitbucketCredentials>,
+    credential: Option<GitCredential>,
+}
+
+impl BitbucketApi {
+    /// Creates a client for the Bitbucket REST API at `api_url`
+    /// (e.g. `https://api.bitbucket.org/2.0/`, or `https://bitbucket.example.com/rest/api/1.0/`).
+    pub fn new(api_url: Url, credentials: Option<BitbucketCredentials>) -> Self {
+        Self {
+            client: Client::new(),
+            api_url,
+            credentials,
+            credential: None,
+        }
+    }
+
+    /// Creates a client for the Bitbucket Cloud or Data Center instance hosting the repository
+    /// (or at `api_url`, if provided),
+    /// authenticated via `BITBUCKET_TOKEN` (and `BITBUCKET_USERNAME`) (or the token file),
+    /// a git credential helper, or a token prompted for, as per `auth`.
+    pub fn authenticated(
+        repository: &GitRepositoryUrl,
+        api_url: Option<Url>,
+        auth: &AuthOptions,
+    ) -> anyhow::Result<Self> {
+        let api_url = match api_url {
+            Some(api_url) => api_url,
+            None => BitbucketRepository::try_from(repository.clone())?.api_url()?,
+        };
+
+        let (credentials, credential) = pick_credentials(&repository.host_url()?, auth)?;
+
+        Ok(Self {
+            credential,
+            ..Self::new(api_url, credentials)
+        })
+    }
+
+    fn get(&self, url: Url) -> RequestBuilder {
+        let request = self.client.get(url);
+
+        match &self.credentials {
+            Some(BitbucketCredentials::Token(token)) => request.bearer_auth(token.expose_secret()),
+            Some(BitbucketCredentials::Basic { username, password }) => {
+                request.basic_auth(username, Some(password.expose_secret()))
+            }
+            None => request,
+        }
+    }
+
+    fn pull_requests_url(&self, repository: &BitbucketRepository) -> anyhow::Result<Url> {
+        let mut url = self.api_url.clone();
+
+        let path: Vec<&str> = match repository {
+            BitbucketRepository::Cloud { workspace, slug } => {
+                vec!["repositories", workspace, slug, "pullrequests"]
+            }
+            BitbucketRepository::DataCenter { project, slug, .. } => {
+                vec!["projects", project, "repos", slug, "pull-requests"]
+            }
+        };
+
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Bitbucket API url: {}", self.api_url))?
+            .pop_if_empty()
+            .extend(path);
+
+        Ok(url)
+    }
+
+    async fn cloud_pull_requests_page(
+        &self,
+        page_url: Url,
+    ) -> anyhow::Result<(Vec<CloudPullRequest>, Option<Url>)> {
+        let page: CloudPage = send_request(self.get(page_url), self.credential.as_ref())
+            .await?
+            .json()
+            .await?;
+
+        let next_page_url = page.next.as_deref().map(Url::parse).transpose()?;
+
+        Ok((page.values, next_page_url))
+    }
+
+    async fn data_center_pull_requests_page(
+        &self,
+        pull_requests_url: &Url,
+        query: &PullRequestQuery,
+        start: u64,
+        order: &str,
+    ) -> anyhow::Result<(Vec<DataCenterPullRequest>, Option<u64>)> {
+        let mut request = self.get(pull_requests_url.clone()).query(&[
+            ("state", "MERGED"),
+            ("order", order),
+            ("limit", DATA_CENTER_PER_PAGE),
+            ("start", &start.to_string()),
+        ]);
+
+        if let Some(base_ref) = &query.base_ref {
+            request = request.query(&[("at", format!("refs/heads/{base_ref}"))]);
+        }
+
+        let page: DataCenterPage = send_request(request, self.credential.as_ref())
+            .await?
+            .json()
+            .await?;
+
+        let next_start = if page.is_last_page {
+            None
+        } else {
+            page.next_page_start
+        };
+
+        Ok((page.values, next_start))
+    }
+
+    fn cloud_pull_requests<'a>(
+        &'a self,
+        pull_requests_url: Url,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let mut filters: Vec<String> = vec![];
+
+        if let Some(base_ref) = &query.base_ref {
+            filters.push(format!("destination.branch.name = {base_ref:?}"));
+        }
+
+        // A pull request can't have been updated any earlier than it got merged:
+        if let Some(merged_after) = query.merged_after {
+            filters.push(format!("updated_on >= {}", merged_after.to_rfc3339()));
+        }
+
+        let mut first_page_url = pull_requests_url;
+        first_page_url
+            .query_pairs_mut()
+            .append_pair("state", "MERGED")
+            .append_pair("sort", "-updated_on")
+            .append_pair("pagelen", CLOUD_PER_PAGE);
+
+        if !filters.is_empty() {
+            first_page_url
+                .query_pairs_mut()
+                .append_pair("q", &filters.join(" AND "));
+        }
+
+        // Subsequent pages are linked to by their preceding page:
+        let pages = stream::try_unfold(Some(first_page_url), move |page_url| async move {
+            let Some(page_url) = page_url else {
+                return Ok::<_, anyhow::Error>(None);
+            };
+
+            let (pull_requests, next_page_url) = self.cloud_pull_requests_page(page_url).await?;
+
+            Ok(Some((pull_requests, next_page_url)))
+        });
+
+        // Bitbucket Cloud doesn't report when a pull request got merged, which is why
+        // the query's time window is only applied via the pull requests' last update.
+        pages
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            .and_then(|pull_request| future::ready(pull_request.try_into()))
+            .boxed()
+    }
+
+    fn data_center_pull_requests<'a>(
+        &'a self,
+        pull_requests_url: Url,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let first_page = (0, DATA_CENTER_ORDER_CLOSED);
+
+        let pages = stream::try_unfold(Some(first_page), move |page| {
+            let pull_requests_url = pull_requests_url.clone();
+            async move {
+                let Some((start, mut order)) = page else {
+                    return Ok::<_, anyhow::Error>(None);
+                };
+
+                let mut result = self
+                    .data_center_pull_requests_page(&pull_requests_url, query, start, order)
+                    .await;
+
+                // Servers that don't support ordering by closed date reject it as a bad request:
+                if order == DATA_CENTER_ORDER_CLOSED && start == 0 && is_bad_request(&result) {
+                    order = DATA_CENTER_ORDER_NEWEST;
+                    result = self
+                        .data_center_pull_requests_page(&pull_requests_url, query, start, order)
+                        .await;
+                }
+
+                let (pull_requests, next_start) = result?;
+                let is_ordered_by_closed_date = order == DATA_CENTER_ORDER_CLOSED;
+
+                let pull_requests = pull_requests
+                    .into_iter()
+                    .map(move |pull_request| (pull_request, is_ordered_by_closed_date));
+
+                let next_page = next_start.map(|next_start| (next_start, order));
+
+                Ok(Some((pull_requests, next_page)))
+            }
+        });
+
+        pages
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            // Ordering by closed date allows for stopping early,
+            // as the pull requests got merged when they got closed:
+            .take_while(move |result| {
+                let is_too_old = match result {
+                    Ok((pull_request, true)) => {
+                        match (pull_request.merged_at(), query.merged_after) {
+                            (Some(merged_at), Some(merged_after)) => merged_at < merged_after,
+                            _ => false,
+                        }
+                    }
+                    _ => false,
+                };
+
+                future::ready(!is_too_old)
+            })
+            .map_ok(|(pull_request, _)| pull_request)
+            .try_filter(move |pull_request| {
+                let is_merged = pull_request
+                    .merged_at()
+                    .is_some_and(|merged_at| query.contains_merged_at(merged_at));
+
+                future::ready(is_merged)
+            })
+            .and_then(|pull_request| future::ready(pull_request.try_into()))
+            .boxed()
+    }
+}
+
+/// Returns `true` if the request failed with a `400 Bad Request` status.
+fn is_bad_request<T>(result: &anyhow::Result<T>) -> bool {
+    let Err(err) = result else {
+        return false;
+    };
+
+    err.downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        .is_some_and(|status| status == StatusCode::BAD_REQUEST)
+}
+
+/// The coordinates of a repository hosted on Bitbucket Cloud or Bitbucket Data Center.
+#[derive(Clone, Debug)]
+pub enum BitbucketRepository {
+    /// A repository on Bitbucket Cloud (e.g. `https://bitbucket.org/{workspace}/{slug}.git`).
+    Cloud { workspace: String, slug: String },
+
+    /// A repository on a Bitbucket Data Center (formerly Server) instance
+    /// (e.g. `https://{host}/scm/{project}/{slug}.git`, or `ssh://git@{host}:7999/{project}/{slug}.git`).
+    DataCenter {
+        /// The url of the instance, including any context path (e.g. `https://{host}/bitbucket`).
+        base_url: Url,
+        /// The project's key, or `~{user}` for personal repositories.
+        project: String,
+        slug: String,
+    },
+}
+
+impl TryFrom<GitRepositoryUrl> for BitbucketRepository {
+    type Error = anyhow::Error;
+
+    fn try_from(repository: GitRepositoryUrl) -> Result<Self, anyhow::Error> {
+        let url = &repository.url_string;
+
+        let Some(host) = repository.parsed_url.host() else {
+            anyhow::bail!("No host found in url: {url}");
+        };
+
+        let segments = repository.path_segments();
+
+        if host == BITBUCKET_HOST {
+            let [workspace, slug] = segments[..] else {
+                anyhow::bail!("Not a Bitbucket Cloud repository url: {url}");
+            };
+
+            return Ok(Self::Cloud {
+                workspace: workspace.to_owned(),
+                slug: slug.to_owned(),
+            });
+        }
+
+        let Some((context_path, project, slug)) = data_center_coordinates(&segments) else {
+            anyhow::bail!("Not a Bitbucket Data Center repository url: {url}");
+        };
+
+        let (scheme, port) = match repository.parsed_url.scheme() {
+            Some(scheme @ ("http" | "https")) => (scheme, repository.parsed_url.port()),
+            _ => ("https", None),
+        };
+
+        let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+        let mut base_url = Url::parse(&format!("{scheme}://{host}{port}/"))?;
+        base_url
+            .path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Bitbucket url: {url}"))?
+            .pop_if_empty()
+            .extend(context_path);
+
+        Ok(Self::DataCenter {
+            base_url,
+            project,
+            slug: slug.to_owned(),
+        })
+    }
+}
+
+impl BitbucketRepository {
+    /// Returns the url of the REST API of the Bitbucket instance hosting the repository.
+    pub fn api_url(&self) -> anyhow::Result<Url> {
+        match self {
+            Self::Cloud { .. } => Ok(Url::parse(BITBUCKET_CLOUD_API_URL)?),
+            Self::DataCenter { base_url, .. } => {
+                let mut api_url = base_url.clone();
+
+                api_url
+                    .path_segments_mut()
+                    .map_err(|_| anyhow::anyhow!("Invalid Bitbucket url: {base_url}"))?
+                    .pop_if_empty()
+                    .extend(["rest", "api", "1.0", ""]);
+
+                Ok(api_url)
+            }
+        }
+    }
+}
+
+/// Extracts the context path, project key and repository slug
+/// from the path segments of a Bitbucket Data Center url.
+///
+/// Supports clone urls (`[context/]scm/{project}/{slug}`, with `scm` being the third to last segment), browser urls
+/// (`[context/]projects/{project}/repos/{slug}/…` and `[context/]users/{user}/repos/{slug}/…`)
+/// as well as ssh urls (`{project}/{slug}`).
+fn data_center_coordinates<'s>(segments: &[&'s str]) -> Option<(Vec<&'s str>, String, &'s str)> {
+    if let [context @ .., "scm", project, slug] = segments {
+        return Some((context.to_vec(), (*project).to_owned(), slug));
+    }
+
+    let browse_index = segments
+        .windows(4)
+        .position(|window| matches!(window, ["projects" | "users", _, "repos", _]));
+
+    if let Some(index) = browse_index {
+        let project = match segments[index] {
+            "users" => format!("~{}", segments[index + 1]),
+            _ => segments[index + 1].to_owned(),
+        };
+
+        return Some((segments[..index].to_vec(), project, segments[index + 3]));
+    }
+
+    if let [project, slug] = segments[..] {
+        return Some((vec![], project.to_owned(), slug));
+    }
+
+    None
+}
+
+impl GitHost for BitbucketApi {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let safe_repository = match BitbucketRepository::try_from(repository.clone()) {
+            Ok(safe_repository) => safe_repository,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        let pull_requests_url = match self.pull_requests_url(&safe_repository) {
+            Ok(pull_requests_url) => pull_requests_url,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        match safe_repository {
+            BitbucketRepository::Cloud { .. } => self.cloud_pull_requests(pull_requests_url, query),
+            BitbucketRepository::DataCenter { .. } => {
+                self.data_center_pull_requests(pull_requests_url, query)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CloudPage {
+    values: Vec<CloudPullRequest>,
+    next: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CloudPullRequest {
+    id: u64,
+    title: Option<String>,
+    author: Option<CloudUser>,
+    destination: CloudEndpoint,
+    merge_commit: Option<CloudCommit>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CloudUser {
+    nickname: Option<String>,
+    display_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CloudEndpoint {
+    branch: CloudBranch,
+    commit: CloudCommit,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CloudBranch {
+    name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct CloudCommit {
+    hash: String,
+}
+
+impl TryFrom<CloudPullRequest> for GitPullRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(pull_request: CloudPullRequest) -> Result<Self, Self::Error> {
+        let Some(merge_commit) = pull_request.merge_commit else {
+            anyhow::bail!("Could not find merge commit sha");
+        };
+
+        Ok(GitPullRequest {
+            identifier: pull_request.id.to_string(),
+            title: pull_request.title,
+            author: pull_request
+                .author
+                .and_then(|author| author.nickname.or(author.display_name)),
+            // Bitbucket has no concept of pull request labels:
+            labels: vec![],
+            base_ref: pull_request.destination.branch.name,
+            base_sha: Some(pull_request.destination.commit.hash),
+            merge_sha: merge_commit.hash,
+            // Bitbucket Cloud doesn't report when a pull request got merged:
+            merged_at: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCenterPage {
+    values: Vec<DataCenterPullRequest>,
+    is_last_page: bool,
+    next_page_start: Option<u64>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCenterPullRequest {
+    id: u64,
+    title: Option<String>,
+    author: Option<DataCenterParticipant>,
+    to_ref: DataCenterRef,
+    /// The point in time the pull request got merged at, in milliseconds since the epoch.
+    closed_date: Option<i64>,
+    #[serde(default)]
+    properties: DataCenterProperties,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct DataCenterParticipant {
+    user: DataCenterUser,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct DataCenterUser {
+    name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCenterRef {
+    display_id: String,
+    latest_commit: String,
+}
+
+#[derive(Clone, Default, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataCenterProperties {
+    merge_commit: Option<DataCenterCommit>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct DataCenterCommit {
+    id: String,
+}
+
+impl DataCenterPullRequest {
+    /// Returns the point in time the (merged) pull request got closed at, which is when it got merged.
+    fn merged_at(&self) -> Option<DateTime<Utc>> {
+        self.closed_date
+            .and_then(|closed_date| Utc.timestamp_millis_opt(closed_date).single())
+    }
+}
+
+impl TryFrom<DataCenterPullRequest> for GitPullRequest {
+    type Error = anyhow::Error;
+
+    fn try_from(pull_request: DataCenterPullRequest) -> Result<Self, Self::Error> {
+        let merged_at = pull_request.merged_at();
+
+        let Some(merge_commit) = pull_request.properties.merge_commit else {
+            anyhow::bail!("Could not find merge commit sha");
+        };
+
+        Ok(GitPullRequest {
+            identifier: pull_request.id.to_string(),
+            title: pull_request.title,
+            author: pull_request.author.map(|author| author.user.name),
+            // Bitbucket has no concept of pull request labels:
+            labels: vec![],
+            base_ref: pull_request.to_ref.display_id,
+            base_sha: Some(pull_request.to_ref.latest_commit),
+            merge_sha: merge_commit.id,
+            merged_at,
+        })
+    }
+}
+
+fn pick_credentials(
+    url: &Url,
+    auth: &AuthOptions,
+) -> anyhow::Result<(Option<BitbucketCredentials>, Option<GitCredential>)> {
+    let username = std::env::var(BITBUCKET_USERNAME_VAR)
+        .ok()
+        .filter(|username| !username.is_empty());
+
+    let token_username = username.as_deref().unwrap_or(ACCESS_TOKEN_USERNAME);
+
+    let token = auth.token_or_prompt(&[BITBUCKET_TOKEN_VAR], url, token_username, || {
+        let token = Password::new("Access token (leave empty for no authentication):")
+            .with_help_message(&format!(
+                "Set {BITBUCKET_USERNAME_VAR} for authenticating with an app password instead"
+            ))
+            .with_display_toggle_enabled()
+            .with_display_mode(PasswordDisplayMode::Hidden)
+            .prompt()?;
+
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SecretString::from(token)))
+    })?;
+
+    let Some(Token { secret, credential }) = token else {
+        return Ok((None, None));
+    };
+
+    // Credentials of git credential helpers come with their own username:
+    let username = match credential.as_ref().and_then(GitCredential::username) {
+        Some(ACCESS_TOKEN_USERNAME) => None,
+        Some(username) => Some(username.to_owned()),
+        None => username,
+    };
+
+    let credentials = match username {
+        Some(username) => BitbucketCredentials::Basic {
+            username,
+            password: secret,
+        },
+        None => BitbucketCredentials::Token(secret),
+    };
+
+    Ok((Some(credentials), credential))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use crate::test_support::{TestResponse, TestServer};
+
+    use super::*;
+
+    fn data_center_pull_request(id: u64, closed_date: &str) -> serde_json::Value {
+        let closed_date: DateTime<Utc> = closed_date.parse().unwrap();
+
+        json!({
+            "id": id,
+            "title": format!("Pull request {id}"),
+            "author": { "user": { "name": "octocat" } },
+            "toRef": { "displayId": "main", "latestCommit": "b".repeat(40) },
+            "closedDate": closed_date.timestamp_millis(),
+            "properties": { "mergeCommit": { "id": format!("{id:040}") } },
+        })
+    }
+
+    fn data_center_page(
+        values: Vec<serde_json::Value>,
+        next_page_start: Option<u64>,
+    ) -> TestResponse {
+        TestResponse::json(
+            200,
+            json!({
+                "values": values,
+                "isLastPage": next_page_start.is_none(),
+                "nextPageStart": next_page_start,
+            }),
+        )
+    }
+
+    async fn data_center_identifiers(server: &TestServer, query: &PullRequestQuery) -> Vec<String> {
+        let api = BitbucketApi::new(server.url().join("rest/api/1.0/").unwrap(), None);
+
+        let repository =
+            GitRepositoryUrl::from_str("https://bitbucket.example.com/scm/PROJ/repo.git").unwrap();
+
+        let pull_requests: Vec<GitPullRequest> = api
+            .merged_pull_requests(&repository, query)
+            .try_collect()
+            .await
+            .unwrap();
+
+        pull_requests
+            .into_iter()
+            .map(|pull_request| pull_request.identifier)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn data_center_pull_requests_stop_once_merged_before_query() {
+        let server = TestServer::start(|request| {
+            assert_eq!(request.query("order").as_deref(), Some("CLOSED_DATE"));
+
+            match request.query("start").as_deref() {
+                Some("0") => data_center_page(
+                    vec![
+                        data_center_pull_request(3, "2026-10-03T00:00:00Z"),
+                        data_center_pull_request(2, "2026-10-02T00:00:00Z"),
+                    ],
+                    Some(2),
+                ),
+                Some("2") => data_center_page(
+                    vec![
+                        data_center_pull_request(1, "2026-10-01T00:00:00Z"),
+                        data_center_pull_request(0, "2026-09-01T00:00:00Z"),
+                    ],
+                    Some(4),
+                ),
+                _ => TestResponse::json(404, json!({})),
+            }
+        });
+
+        let query = PullRequestQuery {
+            merged_after: Some("2026-09-30T00:00:00Z".parse().unwrap()),
+            ..PullRequestQuery::default()
+        };
+
+        assert_eq!(
+            data_center_identifiers(&server, &query).await,
+            ["3", "2", "1"]
+        );
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0]
+            .path
+            .starts_with("/rest/api/1.0/projects/PROJ/repos/repo/pull-requests?"));
+    }
+
+    #[tokio::test]
+    async fn data_center_pull_requests_fall_back_to_newest_order() {
+        let server = TestServer::start(|request| match request.query("order").as_deref() {
+            Some("CLOSED_DATE") => TestResponse::json(400, json!({ "errors": [] })),
+            _ => data_center_page(
+                vec![
+                    data_center_pull_request(2, "2026-09-01T00:00:00Z"),
+                    data_center_pull_request(1, "2026-10-01T00:00:00Z"),
+                ],
+                None,
+            ),
+        });
+
+        let query = PullRequestQuery {
+            merged_after: Some("2026-09-30T00:00:00Z".parse().unwrap()),
+            ..PullRequestQuery::default()
+        };
+
+        // Ordered by creation, older merges don't stop the listing:
+        assert_eq!(data_center_identifiers(&server, &query).await, ["1"]);
+
+        let orders: Vec<Option<String>> = server
+            .requests()
+            .iter()
+            .map(|request| request.query("order"))
+            .collect();
+        assert_eq!(
+            orders,
+            [Some("CLOSED_DATE".to_owned()), Some("NEWEST".to_owned())]
+        );
+    }
+
+    #[test]
+    fn data_center_coordinates_only_match_scm_before_project_and_slug() {
+        assert_eq!(
+            data_center_coordinates(&["bitbucket", "scm", "PROJ", "repo"]),
+            Some((vec!["bitbucket"], "PROJ".to_owned(), "repo"))
+        );
+
+        assert_eq!(
+            data_center_coordinates(&["scm", "group", "subgroup", "repo"]),
+            None
+        );
+    }
+}
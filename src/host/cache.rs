@@ -0,0 +1,463 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use futures::{
+    channel::mpsc::{self, UnboundedSender},
+    future, stream, StreamExt, TryStreamExt,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::host::{GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream};
+
+/// The overlap (in days) between consecutive syncs, to account for clock skew
+/// and for delays between merging and the host reporting the merge.
+const SYNC_TOLERANCE_DAYS: i64 = 1;
+
+/// A host wrapping another host, caching its pull requests on disk.
+///
+/// Only the first request of a repository's pull requests requests all of those matching its query,
+/// while later ones only request those merged since the previous sync (if needed at all),
+/// passing the requested pull requests on as they arrive.
+pub struct CachingHost {
+    host: Option<Box<dyn GitHost + Send + Sync>>,
+    directory: PathBuf,
+    refresh: bool,
+}
+
+impl CachingHost {
+    /// Wraps the host, caching its pull requests in files within `directory`.
+    pub fn new(host: Box<dyn GitHost + Send + Sync>, directory: PathBuf) -> Self {
+        Self {
+            host: Some(host),
+            directory,
+            refresh: false,
+        }
+    }
+
+    /// Creates a host that only serves the pull requests cached within `directory`,
+    /// without requesting any from a host.
+    pub fn offline(directory: PathBuf) -> Self {
+        Self {
+            host: None,
+            directory,
+            refresh: false,
+        }
+    }
+
+    /// Discards any cached pull requests, requesting them all over again.
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Returns the path of the file caching the repository's pull requests.
+    fn cache_path(&self, repository: &GitRepositoryUrl) -> anyhow::Result<PathBuf> {
+        let coordinates = repository.coordinates()?;
+
+        let segments = std::iter::once(&coordinates.host)
+            .chain(&coordinates.namespace)
+            .chain(std::iter::once(&coordinates.name));
+
+        let mut path = self.directory.clone();
+
+        for segment in segments {
+            if segment.is_empty() || segment == "." || segment == ".." || segment.contains('/') {
+                anyhow::bail!("Unsupported repository url: {}", repository.url_string);
+            }
+
+            path.push(segment);
+        }
+
+        path.set_extension("json");
+
+        Ok(path)
+    }
+
+    /// Serves the pull requests cached for the repository, without requesting any from a host.
+    fn offline_pull_requests(
+        &self,
+        repository: &GitRepositoryUrl,
+        query: &PullRequestQuery,
+    ) -> anyhow::Result<Vec<GitPullRequest>> {
+        let path = self.cache_path(repository)?;
+
+        let cache = PullRequestCache::read(&path)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No cached pull requests found for {url}, run without `--offline` first",
+                url = repository.url_string
+            )
+        })?;
+
+        if !cache.covers(query) || !cache.reaches(query) {
+            eprintln!("Warning: Cached pull requests may not cover the entire range, run without `--offline`");
+        }
+
+        let pull_requests = cache
+            .pull_requests
+            .into_iter()
+            .filter(|pull_request| query.matches(pull_request))
+            .collect();
+
+        Ok(pull_requests)
+    }
+
+    /// Requests the pull requests merged since the cache's last sync
+    /// (or all of them matching the query, if there's no usable cache) and merges them into the cache,
+    /// passing those matching the query on via `sender` as they arrive, followed by the cached ones.
+    async fn sync(
+        &self,
+        host: &(dyn GitHost + Send + Sync),
+        repository: &GitRepositoryUrl,
+        query: &PullRequestQuery,
+        sender: &UnboundedSender<anyhow::Result<GitPullRequest>>,
+    ) -> anyhow::Result<()> {
+        let path = self.cache_path(repository)?;
+
+        let cache = if self.refresh {
+            None
+        } else {
+            PullRequestCache::read(&path)?
+        };
+
+        let synced_at = Utc::now();
+
+        // Caches that don't cover the query's base branch or don't reach back far enough get re-synced entirely:
+        let (sync_query, cache) = match cache.filter(|cache| cache.covers(query)) {
+            Some(cache) => {
+                let sync_query = cache.sync_query(query);
+                if sync_query.is_some() {
+                    eprintln!("Requesting pull requests merged since last sync ...");
+                }
+                (sync_query, cache)
+            }
+            None => {
+                let cache = PullRequestCache {
+                    synced_at,
+                    base_ref: query.base_ref.clone(),
+                    merged_after: query.merged_after,
+                    merged_before: query.merged_before,
+                    pull_requests: vec![],
+                };
+                (Some(query.clone()), cache)
+            }
+        };
+
+        let Some(sync_query) = sync_query else {
+            for pull_request in cache.pull_requests {
+                if query.matches(&pull_request) && sender.unbounded_send(Ok(pull_request)).is_err()
+                {
+                    break;
+                }
+            }
+
+            return Ok(());
+        };
+
+        let mut synced: HashMap<String, GitPullRequest> = HashMap::new();
+        let mut pull_requests = host.merged_pull_requests(repository, &sync_query);
+
+        while let Some(pull_request) = pull_requests.try_next().await? {
+            if query.matches(&pull_request) {
+                // Nobody's waiting for the rest, if the receiver is gone:
+                if sender.unbounded_send(Ok(pull_request.clone())).is_err() {
+                    return Ok(());
+                }
+            }
+
+            synced.insert(pull_request.identifier.clone(), pull_request);
+        }
+
+        eprintln!(
+            "Synced {count} pull requests into cache ...",
+            count = synced.len()
+        );
+
+        let cached: Vec<GitPullRequest> = cache
+            .pull_requests
+            .into_iter()
+            .filter(|pull_request| !synced.contains_key(&pull_request.identifier))
+            .collect();
+
+        for pull_request in &cached {
+            if query.matches(pull_request)
+                && sender.unbounded_send(Ok(pull_request.clone())).is_err()
+            {
+                break;
+            }
+        }
+
+        let mut pull_requests: Vec<GitPullRequest> =
+            cached.into_iter().chain(synced.into_values()).collect();
+        pull_requests.sort_by_key(|pull_request| Reverse(pull_request.merged_at));
+
+        let cache = PullRequestCache {
+            synced_at,
+            merged_before: query.merged_before,
+            pull_requests,
+            ..cache
+        };
+
+        cache.write(&path)
+    }
+}
+
+impl GitHost for CachingHost {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let Some(host) = &self.host else {
+            return stream::once(future::ready(self.offline_pull_requests(repository, query)))
+                .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+                .try_flatten()
+                .boxed();
+        };
+
+        // Pull requests get passed on as they arrive, while the sync is still in progress:
+        let (sender, receiver) = mpsc::unbounded();
+
+        let sync = async move {
+            if let Err(err) = self.sync(&**host, repository, query, &sender).await {
+                let _ = sender.unbounded_send(Err(err));
+            }
+        };
+
+        stream::select(receiver.map(Some), stream::once(sync).map(|()| None))
+            .filter_map(future::ready)
+            .boxed()
+    }
+}
+
+/// The cached pull requests of a repository.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PullRequestCache {
+    /// The point in time of the last sync.
+    synced_at: DateTime<Utc>,
+
+    /// The base branch covered by the cache, unless it covers all base branches.
+    #[serde(default)]
+    base_ref: Option<String>,
+
+    /// The earliest point in time covered by the cache, unless it covers all pull requests.
+    merged_after: Option<DateTime<Utc>>,
+
+    /// The latest point in time covered by the cache, unless it covers all pull requests up to its last sync.
+    #[serde(default)]
+    merged_before: Option<DateTime<Utc>>,
+
+    /// The cached pull requests, ordered from most to least recently merged.
+    pull_requests: Vec<GitPullRequest>,
+}
+
+impl PullRequestCache {
+    fn read(path: &Path) -> anyhow::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read_to_string(path)?;
+
+        match serde_json::from_str(&json) {
+            Ok(cache) => Ok(Some(cache)),
+            Err(err) => {
+                eprintln!(
+                    "Warning: Ignoring invalid pull request cache {path}: {err}",
+                    path = path.display()
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    fn write(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// Returns `true` if the cache covers the query's base branch and reaches back far enough for it.
+    fn covers(&self, query: &PullRequestQuery) -> bool {
+        let covers_base_ref = self.base_ref.is_none() || self.base_ref == query.base_ref;
+
+        let covers_merged_after = match (self.merged_after, query.merged_after) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(cached_after), Some(queried_after)) => cached_after <= queried_after,
+        };
+
+        covers_base_ref && covers_merged_after
+    }
+
+    /// Returns `true` if the cache reaches forward far enough for the query,
+    /// not counting any pull requests merged since its last sync.
+    fn reaches(&self, query: &PullRequestQuery) -> bool {
+        match (self.merged_before, query.merged_before) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(cached_before), Some(queried_before)) => queried_before <= cached_before,
+        }
+    }
+
+    /// Returns the query for the pull requests merged since the cache's last sync
+    /// that are needed for the query, unless the cache already covers all of them.
+    fn sync_query(&self, query: &PullRequestQuery) -> Option<PullRequestQuery> {
+        let synced_until = self.synced_at - Duration::days(SYNC_TOLERANCE_DAYS);
+
+        let covered_until = match self.merged_before {
+            Some(merged_before) => merged_before.min(synced_until),
+            None => synced_until,
+        };
+
+        if query
+            .merged_before
+            .is_some_and(|merged_before| merged_before <= covered_until)
+        {
+            return None;
+        }
+
+        Some(PullRequestQuery {
+            base_ref: self.base_ref.clone(),
+            merged_after: Some(covered_until),
+            merged_before: query.merged_before,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        str::FromStr,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::test_support::TestDirectory;
+
+    use super::*;
+
+    /// A host serving a fixed set of pull requests, while recording the queries it got asked.
+    struct RecordingHost {
+        pull_requests: Vec<GitPullRequest>,
+        queries: Arc<Mutex<Vec<PullRequestQuery>>>,
+    }
+
+    impl GitHost for RecordingHost {
+        fn merged_pull_requests<'a>(
+            &'a self,
+            _repository: &'a GitRepositoryUrl,
+            query: &'a PullRequestQuery,
+        ) -> PullRequestStream<'a> {
+            self.queries.lock().unwrap().push(query.clone());
+
+            let pull_requests: Vec<GitPullRequest> = self
+                .pull_requests
+                .iter()
+                .filter(|pull_request| query.matches(pull_request))
+                .cloned()
+                .collect();
+
+            stream::iter(pull_requests).map(Ok).boxed()
+        }
+    }
+
+    fn pull_request(identifier: &str, merged_at: DateTime<Utc>) -> GitPullRequest {
+        GitPullRequest {
+            identifier: identifier.to_owned(),
+            title: None,
+            author: None,
+            labels: vec![],
+            base_ref: "main".to_owned(),
+            base_sha: None,
+            merge_sha: format!("{identifier:0>40}"),
+            merged_at: Some(merged_at),
+        }
+    }
+
+    async fn identifiers(host: &CachingHost, query: &PullRequestQuery) -> Vec<String> {
+        let repository = GitRepositoryUrl::from_str("https://github.com/owner/name.git").unwrap();
+
+        let pull_requests: Vec<GitPullRequest> = host
+            .merged_pull_requests(&repository, query)
+            .try_collect()
+            .await
+            .unwrap();
+
+        pull_requests
+            .into_iter()
+            .map(|pull_request| pull_request.identifier)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_only_requests_those_merged_since_last_sync() {
+        let directory = TestDirectory::new();
+        let now = Utc::now();
+
+        let queries: Arc<Mutex<Vec<PullRequestQuery>>> = Arc::default();
+        let recording_host = || RecordingHost {
+            pull_requests: vec![
+                pull_request("3", now - Duration::days(1)),
+                pull_request("2", now - Duration::days(10)),
+                pull_request("1", now - Duration::days(20)),
+            ],
+            queries: queries.clone(),
+        };
+
+        let first_query = PullRequestQuery {
+            base_ref: Some("main".to_owned()),
+            merged_after: Some(now - Duration::days(30)),
+            merged_before: Some(now - Duration::days(5)),
+        };
+
+        let host = CachingHost::new(Box::new(recording_host()), directory.path().to_owned());
+        assert_eq!(identifiers(&host, &first_query).await, ["2", "1"]);
+
+        // The first sync keeps the query's bounds:
+        let recorded = queries.lock().unwrap().clone();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].base_ref.as_deref(), Some("main"));
+        assert_eq!(recorded[0].merged_before, first_query.merged_before);
+
+        // Queries within the cached range don't request anything:
+        let host = CachingHost::new(Box::new(recording_host()), directory.path().to_owned());
+        assert_eq!(identifiers(&host, &first_query).await, ["2", "1"]);
+        assert_eq!(queries.lock().unwrap().len(), 1);
+
+        let second_query = PullRequestQuery {
+            merged_before: None,
+            ..first_query
+        };
+
+        let host = CachingHost::new(Box::new(recording_host()), directory.path().to_owned());
+        assert_eq!(identifiers(&host, &second_query).await, ["3", "2", "1"]);
+
+        // Later syncs only request those merged since the cached range:
+        let recorded = queries.lock().unwrap().clone();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[1].base_ref.as_deref(), Some("main"));
+        assert_eq!(recorded[1].merged_after, first_query.merged_before);
+        assert_eq!(recorded[1].merged_before, None);
+
+        // Caches of another base branch don't cover the query:
+        let other_query = PullRequestQuery {
+            base_ref: Some("release".to_owned()),
+            ..second_query
+        };
+
+        let host = CachingHost::new(Box::new(recording_host()), directory.path().to_owned());
+        assert!(identifiers(&host, &other_query).await.is_empty());
+        assert_eq!(
+            queries.lock().unwrap()[2].base_ref.as_deref(),
+            Some("release")
+        );
+    }
+}
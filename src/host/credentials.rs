@@ -0,0 +1,233 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use secrecy::{ExposeSecret, SecretString};
+use url::Url;
+
+/// Credentials for a host, as provided by (or to be stored via) git's credential helpers
+/// (see `git help credential`).
+#[derive(Clone, Debug)]
+pub(crate) struct GitCredential {
+    protocol: String,
+    host: String,
+    username: Option<String>,
+    password: SecretString,
+
+    /// Whether the credentials got prompted for (instead of provided by the git credential helpers),
+    /// which only those get approved (or rejected) by `review`.
+    prompted: bool,
+
+    reviewed: Arc<AtomicBool>,
+}
+
+impl GitCredential {
+    /// Creates credentials for the host of `url` (e.g. a token that got prompted for),
+    /// to be stored once the host accepted them.
+    pub(crate) fn new(url: &Url, username: &str, password: SecretString) -> anyhow::Result<Self> {
+        let (protocol, host) = protocol_and_host(url)?;
+
+        Ok(Self {
+            protocol,
+            host,
+            username: Some(username.to_owned()),
+            password,
+            prompted: true,
+            reviewed: Arc::default(),
+        })
+    }
+
+    /// Asks the git credential helpers for credentials for the host of `url`,
+    /// without letting git (or the helpers) prompt for them.
+    ///
+    /// Returns `None` if there are none (or git isn't available).
+    pub(crate) fn fill(url: &Url) -> anyhow::Result<Option<Self>> {
+        let (protocol, host) = protocol_and_host(url)?;
+
+        let input = format!("protocol={protocol}\nhost={host}\n\n");
+
+        let Some(output) = git_credential("fill", &input) else {
+            return Ok(None);
+        };
+
+        let mut username: Option<String> = None;
+        let mut password: Option<String> = None;
+
+        for line in output.lines() {
+            match line.split_once('=') {
+                Some(("username", value)) if !value.is_empty() => username = Some(value.to_owned()),
+                Some(("password", value)) if !value.is_empty() => password = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+
+        let Some(password) = password else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            protocol,
+            host,
+            username,
+            password: SecretString::from(password),
+            prompted: false,
+            reviewed: Arc::default(),
+        }))
+    }
+
+    pub(crate) fn host(&self) -> &str {
+        &self.host
+    }
+
+    #[cfg_attr(not(feature = "bitbucket"), allow(dead_code))]
+    pub(crate) fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    pub(crate) fn password(&self) -> &SecretString {
+        &self.password
+    }
+
+    /// Stores the credentials via the git credential helpers (i.e. `git credential approve`).
+    pub(crate) fn approve(&self) {
+        if git_credential("approve", &self.input()).is_none() {
+            eprintln!(
+                "Warning: Failed to store credentials for {host} via git credential helper",
+                host = self.host
+            );
+        }
+    }
+
+    /// Erases the credentials from the git credential helpers (i.e. `git credential reject`).
+    pub(crate) fn reject(&self) {
+        if git_credential("reject", &self.input()).is_none() {
+            eprintln!(
+                "Warning: Failed to erase credentials for {host} via git credential helper",
+                host = self.host
+            );
+        }
+    }
+
+    /// Reports the status of the first response to a request authenticated with prompted credentials
+    /// to the git credential helpers, the way git does: approving them once they got accepted,
+    /// or rejecting them once they got refused (i.e. `401 Unauthorized`).
+    ///
+    /// Credentials provided by the git credential helpers are left to them (and to git),
+    /// as a refusal (e.g. due to the token's scopes) doesn't mean they're invalid for git itself.
+    pub(crate) fn review(&self, status: u16) {
+        let (accepted, refused) = ((200..300).contains(&status), status == 401);
+
+        if !self.prompted || !(accepted || refused) || self.reviewed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        if accepted {
+            self.approve();
+        } else {
+            eprintln!(
+                "Warning: {host} refused the credentials, erasing them from the git credential helper",
+                host = self.host
+            );
+            self.reject();
+        }
+    }
+
+    fn input(&self) -> String {
+        let mut input = format!(
+            "protocol={protocol}\nhost={host}\n",
+            protocol = self.protocol,
+            host = self.host
+        );
+
+        if let Some(username) = &self.username {
+            input.push_str(&format!("username={username}\n"));
+        }
+
+        input.push_str(&format!(
+            "password={password}\n\n",
+            password = self.password.expose_secret()
+        ));
+
+        input
+    }
+}
+
+/// Reports the status of a response to the credentials the request got authenticated with, if any.
+pub(crate) fn review_credential(credential: Option<&GitCredential>, status: u16) {
+    if let Some(credential) = credential {
+        credential.review(status);
+    }
+}
+
+/// Sends the request, reporting the response's status to the git credentials it's authenticated with, if any.
+#[cfg(feature = "reqwest")]
+pub(crate) async fn send_request(
+    request: reqwest::RequestBuilder,
+    credential: Option<&GitCredential>,
+) -> anyhow::Result<reqwest::Response> {
+    let response = request.send().await?;
+
+    review_credential(credential, response.status().as_u16());
+
+    Ok(response.error_for_status()?)
+}
+
+/// Returns `true` if a git credential helper is configured for the host of `url`,
+/// which credentials can be stored via.
+pub(crate) fn has_credential_helper(url: &Url) -> bool {
+    Command::new("git")
+        .args([
+            "config",
+            "--get-urlmatch",
+            "credential.helper",
+            url.as_str(),
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Runs `git credential <action>` with the given input, returning its output,
+/// unless it failed.
+fn git_credential(action: &str, input: &str) -> Option<String> {
+    let mut child = Command::new("git")
+        .args(["credential", action])
+        // Neither git, nor any helper (e.g. Git Credential Manager) may prompt for credentials:
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GCM_INTERACTIVE", "never")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    child.stdin.take()?.write_all(input.as_bytes()).ok()?;
+
+    let output = child.wait_with_output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Returns the protocol and host (including any port) that git credentials get looked up by.
+fn protocol_and_host(url: &Url) -> anyhow::Result<(String, String)> {
+    let Some(host) = url.host_str() else {
+        anyhow::bail!("No host found in url: {url}");
+    };
+
+    let host = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_owned(),
+    };
+
+    Ok((url.scheme().to_owned(), host))
+}
@@ -0,0 +1,161 @@
+use std::{fs, path::Path};
+
+use futures::{stream, StreamExt};
+
+use crate::host::{GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream};
+
+/// A host serving a fixed set of pull requests (e.g. loaded from a file),
+/// for reproducible runs without any network access.
+#[derive(Clone, Default, Debug)]
+pub struct FileHost {
+    pull_requests: Vec<GitPullRequest>,
+}
+
+impl FileHost {
+    /// Creates a host serving the given pull requests.
+    pub fn new(pull_requests: Vec<GitPullRequest>) -> Self {
+        Self { pull_requests }
+    }
+
+    /// Creates a host serving the pull requests of a JSON file,
+    /// as written by `git assist pull-requests export`.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path).map_err(|err| {
+            anyhow::anyhow!(
+                "Could not read pull requests file {path}: {err}",
+                path = path.display()
+            )
+        })?;
+
+        let pull_requests = serde_json::from_str(&json).map_err(|err| {
+            anyhow::anyhow!(
+                "Invalid pull requests file {path}: {err}",
+                path = path.display()
+            )
+        })?;
+
+        Ok(Self::new(pull_requests))
+    }
+}
+
+impl GitHost for FileHost {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        _repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let pull_requests = self
+            .pull_requests
+            .iter()
+            .filter(move |pull_request| query.matches(pull_request))
+            .cloned()
+            .map(Ok);
+
+        stream::iter(pull_requests).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use git2::{Oid, Repository, Signature};
+
+    use crate::{
+        command::bisect::{skip_pull_requests, SkipPullRequestsConfig},
+        host::{AuthOptions, PullRequestFilter, PullRequestFilters},
+        test_support::TestDirectory,
+    };
+
+    use super::*;
+
+    fn commit(repository: &Repository, message: &str, parents: &[Oid]) -> Oid {
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let tree = repository
+            .find_tree(repository.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|&parent| repository.find_commit(parent).unwrap())
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+
+        repository
+            .commit(None, &signature, &signature, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn pull_request(identifier: &str, label: &str, base: Oid, merge: Oid) -> GitPullRequest {
+        GitPullRequest {
+            identifier: identifier.to_owned(),
+            title: Some(format!("Pull request {identifier}")),
+            author: None,
+            labels: vec![label.to_owned()],
+            base_ref: "main".to_owned(),
+            base_sha: Some(base.to_string()),
+            merge_sha: merge.to_string(),
+            merged_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn skip_pull_requests_skips_commits_of_matching_pull_requests() {
+        let directory = TestDirectory::new();
+        let repository = Repository::init(directory.path()).unwrap();
+
+        let good = commit(&repository, "Initial commit", &[]);
+        let first_start = commit(&repository, "Start first feature", &[good]);
+        let first_end = commit(&repository, "Finish first feature", &[first_start]);
+        let first_merge = commit(&repository, "Merge first feature", &[good, first_end]);
+        let second = commit(&repository, "Second feature", &[first_merge]);
+        let bad = commit(&repository, "Merge second feature", &[first_merge, second]);
+
+        std::fs::write(repository.path().join("BISECT_START"), "main\n").unwrap();
+
+        let host = FileHost::new(vec![
+            pull_request("2", "wip", first_merge, bad),
+            pull_request("1", "bug", good, first_merge),
+        ]);
+
+        let config = SkipPullRequestsConfig {
+            repository: GitRepositoryUrl::from_str("https://github.com/owner/name.git").unwrap(),
+            directory: directory.path().to_owned(),
+            good: vec![good.to_string()],
+            bad: bad.to_string(),
+            base_branch: Some("main".to_owned()),
+            filters: PullRequestFilters {
+                exclude: vec![PullRequestFilter::from_str("label:wip").unwrap()],
+                ..PullRequestFilters::default()
+            },
+            host_type: None,
+            api_url: None,
+            graphql: false,
+            cache_directory: directory.path().join("cache"),
+            cache: false,
+            refresh: false,
+            offline: false,
+            pull_request_refs: false,
+            merge_messages: false,
+            merge_message_patterns: vec![],
+            pull_requests_file: None,
+            auth: AuthOptions::default(),
+            dry_run: false,
+        };
+
+        let status = skip_pull_requests(&host, &config).await.unwrap();
+        assert!(status.success());
+
+        let mut skipped: Vec<Oid> = repository
+            .references_glob("refs/bisect/skip-*")
+            .unwrap()
+            .map(|reference| reference.unwrap().target().unwrap())
+            .collect();
+        skipped.sort();
+
+        // Only the matching pull request's internal commits get skipped:
+        let mut expected = vec![first_start, first_end];
+        expected.sort();
+
+        assert_eq!(skipped, expected);
+    }
+}
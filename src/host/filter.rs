@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::host::GitPullRequest;
+
+/// A criterion for matching pull requests.
+///
+/// Parsed from `<kind>:<value>`, with `kind` being one of
+/// `label`, `author`, `title` (a regular expression), or `number`.
+/// A plain number (optionally prefixed with `#`) is matched as `number`.
+#[derive(Clone, Debug)]
+pub enum PullRequestFilter {
+    /// Matches pull requests having the given label.
+    Label(String),
+
+    /// Matches pull requests authored by the given user.
+    Author(String),
+
+    /// Matches pull requests with a title matching the given regular expression.
+    Title(Regex),
+
+    /// Matches the pull request with the given number.
+    Number(String),
+}
+
+impl PullRequestFilter {
+    pub fn matches(&self, pull_request: &GitPullRequest) -> bool {
+        match self {
+            Self::Label(label) => pull_request.labels.iter().any(|name| name == label),
+            Self::Author(author) => pull_request.author.as_ref() == Some(author),
+            Self::Title(regex) => pull_request
+                .title
+                .as_deref()
+                .is_some_and(|title| regex.is_match(title)),
+            Self::Number(number) => &pull_request.identifier == number,
+        }
+    }
+}
+
+impl FromStr for PullRequestFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let number = str.strip_prefix('#').unwrap_or(str);
+
+        if !number.is_empty() && number.chars().all(|char| char.is_ascii_digit()) {
+            return Ok(Self::Number(number.to_owned()));
+        }
+
+        let Some((kind, value)) = str.split_once(':') else {
+            anyhow::bail!("Invalid pull request filter {str:?}, expected `<kind>:<value>`");
+        };
+
+        match kind {
+            "label" => Ok(Self::Label(value.to_owned())),
+            "author" => Ok(Self::Author(value.to_owned())),
+            "title" => Ok(Self::Title(Regex::new(value)?)),
+            "number" => Ok(Self::Number(
+                value.strip_prefix('#').unwrap_or(value).to_owned(),
+            )),
+            _ => anyhow::bail!(
+                "Unknown pull request filter kind {kind:?}, expected one of `label`, `author`, `title`, `number`"
+            ),
+        }
+    }
+}
+
+/// A set of filters for selecting pull requests.
+#[derive(Clone, Default, Debug)]
+pub struct PullRequestFilters {
+    /// Filters of which a pull request has to match at least one (unless empty).
+    pub include: Vec<PullRequestFilter>,
+
+    /// Filters of which a pull request must not match any.
+    pub exclude: Vec<PullRequestFilter>,
+}
+
+impl PullRequestFilters {
+    pub fn matches(&self, pull_request: &GitPullRequest) -> bool {
+        let is_included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|filter| filter.matches(pull_request));
+
+        let is_excluded = self
+            .exclude
+            .iter()
+            .any(|filter| filter.matches(pull_request));
+
+        is_included && !is_excluded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pull_request(
+        identifier: &str,
+        title: &str,
+        author: &str,
+        labels: &[&str],
+    ) -> GitPullRequest {
+        GitPullRequest {
+            identifier: identifier.to_owned(),
+            title: Some(title.to_owned()),
+            author: Some(author.to_owned()),
+            labels: labels.iter().map(|&label| label.to_owned()).collect(),
+            base_ref: "main".to_owned(),
+            base_sha: None,
+            merge_sha: "0000000000000000000000000000000000000000".to_owned(),
+            merged_at: None,
+        }
+    }
+
+    fn filter(str: &str) -> PullRequestFilter {
+        str.parse().unwrap()
+    }
+
+    #[test]
+    fn from_str_parses_kinds() {
+        assert!(matches!(filter("label:bug"), PullRequestFilter::Label(label) if label == "bug"));
+        assert!(
+            matches!(filter("author:alice"), PullRequestFilter::Author(author) if author == "alice")
+        );
+        assert!(
+            matches!(filter("title:^fix(es)?:"), PullRequestFilter::Title(regex) if regex.as_str() == "^fix(es)?:")
+        );
+        assert!(matches!(filter("number:42"), PullRequestFilter::Number(number) if number == "42"));
+        assert!(
+            matches!(filter("number:#42"), PullRequestFilter::Number(number) if number == "42")
+        );
+        assert!(matches!(filter("42"), PullRequestFilter::Number(number) if number == "42"));
+        assert!(matches!(filter("#42"), PullRequestFilter::Number(number) if number == "42"));
+    }
+
+    #[test]
+    fn from_str_splits_at_first_colon() {
+        assert!(
+            matches!(filter("label:area:git"), PullRequestFilter::Label(label) if label == "area:git")
+        );
+    }
+
+    #[test]
+    fn from_str_rejects_invalid_filters() {
+        for str in ["", "#", "bug", "#4a", "milestone:1", "title:("] {
+            assert!(str.parse::<PullRequestFilter>().is_err(), "{str:?}");
+        }
+    }
+
+    #[test]
+    fn filter_matches() {
+        let pull_request = pull_request("7", "fix: Crash on start", "alice", &["bug", "ui"]);
+
+        assert!(filter("label:ui").matches(&pull_request));
+        assert!(!filter("label:feature").matches(&pull_request));
+        assert!(filter("author:alice").matches(&pull_request));
+        assert!(!filter("author:bob").matches(&pull_request));
+        assert!(filter("title:(?i)crash").matches(&pull_request));
+        assert!(!filter("title:^feat").matches(&pull_request));
+        assert!(filter("#7").matches(&pull_request));
+        assert!(!filter("number:70").matches(&pull_request));
+    }
+
+    #[test]
+    fn filters_include_any_and_exclude_none() {
+        let bug = pull_request("1", "Fix bug", "alice", &["bug"]);
+        let wip_bug = pull_request("2", "Fix other bug", "bob", &["bug", "wip"]);
+        let feature = pull_request("3", "Add feature", "alice", &["feature"]);
+
+        let filters = PullRequestFilters::default();
+        assert!([&bug, &wip_bug, &feature]
+            .iter()
+            .all(|pr| filters.matches(pr)));
+
+        let filters = PullRequestFilters {
+            include: vec![filter("label:bug"), filter("#3")],
+            exclude: vec![],
+        };
+        assert!(filters.matches(&bug));
+        assert!(filters.matches(&wip_bug));
+        assert!(filters.matches(&feature));
+
+        let filters = PullRequestFilters {
+            include: vec![filter("label:bug")],
+            exclude: vec![filter("label:wip"), filter("author:carol")],
+        };
+        assert!(filters.matches(&bug));
+        assert!(!filters.matches(&wip_bug));
+        assert!(!filters.matches(&feature));
+
+        let filters = PullRequestFilters {
+            include: vec![],
+            exclude: vec![filter("author:alice")],
+        };
+        assert!(!filters.matches(&bug));
+        assert!(filters.matches(&wip_bug));
+        assert!(!filters.matches(&feature));
+    }
+}
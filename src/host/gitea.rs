@@ -0,0 +1,451 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use git_url_parse::GitUrl;
+use inquire::{Password, PasswordDisplayMode};
+use reqwest::{Client, RequestBuilder, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::host::{
+    credentials::{send_request, GitCredential},
+    AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream,
+    Token, TOKEN_USERNAME,
+};
+
+/// The environment variable to read a Gitea/Forgejo access token from.
+const GITEA_TOKEN_VAR: &str = "GITEA_TOKEN";
+
+/// The maximum page size allowed by Gitea's default configuration
+/// (instances may limit it further, via `MAX_RESPONSE_ITEMS`).
+const PER_PAGE: usize = 50;
+
+/// The header linking to the next page of results (if any), among others.
+const LINK_HEADER: &str = "link";
+
+/// The time to wait for a response when probing whether a host runs Gitea/Forgejo.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct GiteaApi {
+    client: Client,
+    api_url: Url,
+    token: Option<SecretString>,
+    credential: Option<GitCredential>,
+}
+
+impl GiteaApi {
+    /// Creates a client for the Gitea/Forgejo REST API at `api_url` (e.g. `https://codeberg.org/api/v1/`).
+    pub fn new(api_url: Url, token: Option<SecretString>) -> Self {
+        Self {
+            client: Client::new(),
+            api_url,
+            token,
+            credential: None,
+        }
+    }
+
+    /// Creates a client for the Gitea/Forgejo instance hosting the repository
+    /// (or at `api_url`, if provided),
+    /// authenticated via `GITEA_TOKEN` (or the token file),
+    /// a git credential helper, or a token prompted for, as per `auth`.
+    pub fn authenticated(
+        repository: &GitRepositoryUrl,
+        api_url: Option<Url>,
+        auth: &AuthOptions,
+    ) -> anyhow::Result<Self> {
+        let api_url = match api_url {
+            Some(api_url) => api_url,
+            None => GiteaRepository::try_from(repository.clone())?.api_url()?,
+        };
+
+        let credential_url = repository.host_url()?;
+        let (token, credential) = Token::split(pick_token(&credential_url, auth)?);
+
+        Ok(Self {
+            credential,
+            ..Self::new(api_url, token)
+        })
+    }
+
+    fn get(&self, url: Url) -> RequestBuilder {
+        let request = self.client.get(url);
+
+        match &self.token {
+            Some(token) => request.header(
+                "Authorization",
+                format!("token {token}", token = token.expose_secret()),
+            ),
+            None => request,
+        }
+    }
+
+    fn pulls_url(&self, repository: &GiteaRepository) -> anyhow::Result<Url> {
+        let mut url = self.api_url.clone();
+
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Gitea API url: {}", self.api_url))?
+            .pop_if_empty()
+            .extend(["repos", &repository.owner()?, &repository.name()?, "pulls"]);
+
+        Ok(url)
+    }
+
+    /// Requests a page of pull requests, returning them along with the number of the next page, if any.
+    async fn pulls_page(
+        &self,
+        pulls_url: &Url,
+        page: usize,
+    ) -> anyhow::Result<(Vec<PullRequest>, Option<usize>)> {
+        let request = self.get(pulls_url.clone()).query(&[
+            ("state", "closed"),
+            ("sort", "recentupdate"),
+            ("limit", &PER_PAGE.to_string()),
+            ("page", &page.to_string()),
+        ]);
+
+        let response = send_request(request, self.credential.as_ref()).await?;
+
+        let next_page = response
+            .headers()
+            .get(LINK_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(next_page);
+
+        let pull_requests = response.json().await?;
+
+        Ok((pull_requests, next_page))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GiteaRepository(GitRepositoryUrl);
+
+impl TryFrom<GitRepositoryUrl> for GiteaRepository {
+    type Error = anyhow::Error;
+
+    fn try_from(repository: GitRepositoryUrl) -> Result<Self, anyhow::Error> {
+        let url = &repository.url_string;
+
+        if repository.parsed_url.host().is_none() {
+            anyhow::bail!("No host found in url: {url}");
+        }
+
+        if repository.coordinates()?.namespace.len() != 1 {
+            anyhow::bail!("Not a Gitea repository url: {url}");
+        }
+
+        Ok(Self(repository))
+    }
+}
+
+impl GiteaRepository {
+    pub fn owner(&self) -> Result<String, anyhow::Error> {
+        Ok(self.0.coordinates()?.namespace[0].clone())
+    }
+
+    pub fn name(&self) -> Result<String, anyhow::Error> {
+        Ok(self.0.coordinates()?.name)
+    }
+
+    /// Returns the url of the REST API of the Gitea/Forgejo instance hosting the repository.
+    pub fn api_url(&self) -> anyhow::Result<Url> {
+        api_url(&self.0.parsed_url)
+    }
+}
+
+/// Returns `true` if the url's host responds like a Gitea/Forgejo instance.
+///
+/// As Gitea/Forgejo instances are self-hosted under arbitrary hostnames,
+/// they get detected by probing their API's version endpoint.
+pub(crate) async fn is_gitea_host(url: &GitUrl) -> bool {
+    let Ok(version_url) = api_url(url).and_then(|api_url| Ok(api_url.join("version")?)) else {
+        return false;
+    };
+
+    let Ok(client) = Client::builder().timeout(PROBE_TIMEOUT).build() else {
+        return false;
+    };
+
+    let Ok(response) = client.get(version_url).send().await else {
+        return false;
+    };
+
+    if !response.status().is_success() {
+        return false;
+    }
+
+    response.json::<ServerVersion>().await.is_ok()
+}
+
+/// Returns the url of the REST API of a Gitea/Forgejo instance hosting the url.
+fn api_url(url: &GitUrl) -> anyhow::Result<Url> {
+    let host = url
+        .host()
+        .ok_or_else(|| anyhow::anyhow!("No host found in url: {url}"))?;
+
+    let (scheme, port) = match url.scheme() {
+        Some(scheme @ ("http" | "https")) => (scheme, url.port()),
+        _ => ("https", None),
+    };
+
+    let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+    Ok(Url::parse(&format!("{scheme}://{host}{port}/api/v1/"))?)
+}
+
+impl GitHost for GiteaApi {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let pulls_url = GiteaRepository::try_from(repository.clone())
+            .and_then(|safe_repository| self.pulls_url(&safe_repository));
+
+        let pulls_url = match pulls_url {
+            Ok(pulls_url) => pulls_url,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        let pages = stream::try_unfold(Some(1), move |page| {
+            let pulls_url = pulls_url.clone();
+            async move {
+                let Some(page) = page else {
+                    return Ok::<_, anyhow::Error>(None);
+                };
+
+                let (pull_requests, next_page) = self.pulls_page(&pulls_url, page).await?;
+
+                Ok(Some((pull_requests, next_page)))
+            }
+        });
+
+        pages
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            // Sorting by last update allows for stopping early, as a pull request
+            // can't have been updated any earlier than it got merged:
+            .take_while(move |result| {
+                let is_too_old = match result {
+                    Ok(pull_request) => match (pull_request.updated_at, query.merged_after) {
+                        (Some(updated_at), Some(merged_after)) => updated_at < merged_after,
+                        _ => false,
+                    },
+                    Err(_) => false,
+                };
+
+                future::ready(!is_too_old)
+            })
+            .try_filter(move |pull_request| {
+                let is_merged = pull_request.merged
+                    && pull_request
+                        .merged_at
+                        .is_some_and(|merged_at| query.contains_merged_at(merged_at));
+
+                let is_on_base = query
+                    .base_ref
+                    .as_ref()
+                    .is_none_or(|base_ref| &pull_request.base.ref_field == base_ref);
+
+                future::ready(is_merged && is_on_base)
+            })
+            .and_then(|pull_request| future::ready(git_pull_request(pull_request)))
+            .boxed()
+    }
+}
+
+/// Returns the number of the next page, as linked to by a `Link` header
+/// (e.g. `<https://codeberg.org/api/v1/repos/owner/name/pulls?page=2>; rel="next", …`).
+///
+/// Only the page number gets picked from the link, so that the next page gets requested
+/// relative to the API's url, which the client only sends its credentials to.
+fn next_page(link: &str) -> Option<usize> {
+    link.split(',').find_map(|link| {
+        let (url, params) = link.split_once(';')?;
+
+        let is_next = params
+            .split(';')
+            .any(|param| param.trim() == r#"rel="next""#);
+
+        if !is_next {
+            return None;
+        }
+
+        let url = Url::parse(url.trim().strip_prefix('<')?.strip_suffix('>')?).ok()?;
+
+        url.query_pairs()
+            .find(|(key, _)| key == "page")
+            .and_then(|(_, value)| value.parse().ok())
+    })
+}
+
+fn git_pull_request(pull_request: PullRequest) -> anyhow::Result<GitPullRequest> {
+    let Some(merge_sha) = pull_request.merge_commit_sha else {
+        anyhow::bail!("Could not find merge commit sha");
+    };
+
+    Ok(GitPullRequest {
+        identifier: pull_request.number.to_string(),
+        title: pull_request.title,
+        author: pull_request.user.map(|user| user.login),
+        labels: pull_request
+            .labels
+            .into_iter()
+            .map(|label| label.name)
+            .collect(),
+        base_ref: pull_request.base.ref_field,
+        base_sha: Some(pull_request.base.sha),
+        merge_sha,
+        merged_at: pull_request.merged_at,
+    })
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct PullRequest {
+    number: u64,
+    title: Option<String>,
+    user: Option<User>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    base: Branch,
+    merge_commit_sha: Option<String>,
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Branch {
+    #[serde(rename = "ref")]
+    ref_field: String,
+    sha: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ServerVersion {
+    #[serde(rename = "version")]
+    _version: String,
+}
+
+fn pick_token(url: &Url, auth: &AuthOptions) -> anyhow::Result<Option<Token>> {
+    auth.token_or_prompt(&[GITEA_TOKEN_VAR], url, TOKEN_USERNAME, || {
+        let token = Password::new("Access token (leave empty for no authentication):")
+            .with_display_toggle_enabled()
+            .with_display_mode(PasswordDisplayMode::Hidden)
+            .prompt()?;
+
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SecretString::from(token)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use crate::test_support::{TestResponse, TestServer};
+
+    use super::*;
+
+    fn pull_request(number: u64, merged_at: &str) -> serde_json::Value {
+        json!({
+            "number": number,
+            "title": format!("Pull request {number}"),
+            "user": { "login": "octocat" },
+            "labels": [{ "name": "bug" }],
+            "base": { "ref": "main", "sha": "b".repeat(40) },
+            "merge_commit_sha": format!("{number:040}"),
+            "merged": true,
+            "merged_at": merged_at,
+            "updated_at": merged_at,
+        })
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_follows_next_links_of_short_pages() {
+        let server = TestServer::start(|request| {
+            let link = |page: usize, rel: &str| {
+                format!(
+                    "<https://gitea.example.com/api/v1/repos/owner/name/pulls?limit=2&page={page}&state=closed>; rel=\"{rel}\""
+                )
+            };
+
+            // Pages are shorter than requested, as if limited via `MAX_RESPONSE_ITEMS`:
+            match request.query("page").as_deref() {
+                Some("1") => TestResponse::json(
+                    200,
+                    json!([
+                        pull_request(3, "2026-10-03T00:00:00Z"),
+                        pull_request(2, "2026-10-02T00:00:00Z"),
+                    ]),
+                )
+                .header(
+                    "Link",
+                    &format!(
+                        "{next},{last}",
+                        next = link(2, "next"),
+                        last = link(2, "last")
+                    ),
+                ),
+                Some("2") => {
+                    TestResponse::json(200, json!([pull_request(1, "2026-10-01T00:00:00Z")]))
+                        .header(
+                            "Link",
+                            &format!(
+                                "{first},{prev}",
+                                first = link(1, "first"),
+                                prev = link(1, "prev")
+                            ),
+                        )
+                }
+                _ => TestResponse::json(404, json!({ "message": "Not Found" })),
+            }
+        });
+
+        let api = GiteaApi::new(
+            server.url().join("api/v1/").unwrap(),
+            Some(SecretString::from("secret")),
+        );
+
+        let repository =
+            GitRepositoryUrl::from_str("https://gitea.example.com/owner/name.git").unwrap();
+
+        let pull_requests: Vec<GitPullRequest> = api
+            .merged_pull_requests(&repository, &PullRequestQuery::default())
+            .try_collect()
+            .await
+            .unwrap();
+
+        let identifiers: Vec<&str> = pull_requests
+            .iter()
+            .map(|pull_request| pull_request.identifier.as_str())
+            .collect();
+        assert_eq!(identifiers, ["3", "2", "1"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+
+        for request in &requests {
+            assert!(request.path.starts_with("/api/v1/repos/owner/name/pulls?"));
+            assert_eq!(request.header("Authorization"), Some("token secret"));
+        }
+    }
+}
@@ -1,49 +1,181 @@
-use std::str::FromStr;
+mod cli;
+mod device;
+mod graphql;
+mod login;
+mod retry;
 
-use async_trait::async_trait;
-use git_url_parse::types::provider::GenericProvider;
+use std::{fs, path::PathBuf, str::FromStr};
+
+use futures::{future, stream, StreamExt, TryStreamExt};
 use inquire::{Password, PasswordDisplayMode, Select, Text};
 use jsonwebtoken::EncodingKey;
 use octocrab::{
     auth::{AppAuth, Auth as GithubAuthentication},
-    models::AppId,
-    params::{pulls::Sort, Direction, State},
-    Octocrab, OctocrabBuilder,
+    models::{pulls::PullRequest, AppId, InstallationId},
+    Octocrab, OctocrabBuilder, Page,
 };
 use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use url::{form_urlencoded, Url};
+
+pub use self::graphql::GithubGraphqlApi;
+
+use self::{
+    cli::gh_token,
+    device::request_device_token,
+    login::{store_token, stored_token},
+    retry::{get_page, wait_for_reset},
+};
+
+use crate::host::{
+    credentials::GitCredential, AuthMethod, AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl,
+    PullRequestQuery, PullRequestStream, GITHUB_HOST, TOKEN_USERNAME,
+};
+
+/// The environment variables to read a Github access token from, in order of precedence
+/// (shared with the Github CLI).
+const GITHUB_TOKEN_VARS: &[&str] = &["GH_TOKEN", "GITHUB_TOKEN"];
 
-use crate::host::{GitHost, GitPullRequest, GitRepositoryUrl, GITHUB_HOST};
+/// The scope to request for access tokens of the OAuth device flow (i.e. access to private repositories).
+pub const DEFAULT_OAUTH_SCOPE: &str = "repo";
+
+/// The url of the Github REST API.
+const GITHUB_API_URL: &str = "https://api.github.com/";
 
 #[derive(Clone, Default, Debug)]
 pub struct GithubApi {
     api: Octocrab,
+    credential: Option<GitCredential>,
 }
 
 impl GithubApi {
-    pub fn authenticated() -> anyhow::Result<Self> {
-        let mut builder = OctocrabBuilder::default();
-
-        builder = match pick_authentication()? {
-            GithubAuthentication::None => builder,
-            GithubAuthentication::Basic { username, password } => {
-                builder.basic_auth(username, password)
-            }
-            GithubAuthentication::PersonalToken(token) => {
-                builder.personal_token(token.expose_secret().to_owned())
-            }
-            GithubAuthentication::App(AppAuth { app_id, key }) => builder.app(app_id, key),
-            GithubAuthentication::OAuth(oauth) => builder.oauth(oauth),
-            GithubAuthentication::UserAccessToken(token) => {
-                builder.user_access_token(token.expose_secret().to_owned())
-            }
+    /// Creates a client for the Github (Enterprise Server) instance hosting the repository,
+    /// or at `api_url`, if provided, authenticated as per `auth`.
+    pub async fn authenticated(
+        repository: &GitRepositoryUrl,
+        api_url: Option<Url>,
+        auth: &AuthOptions,
+    ) -> anyhow::Result<Self> {
+        let api_url = match api_url {
+            Some(api_url) => api_url,
+            None => GithubRepository::try_from(repository.clone())?.api_url()?,
         };
 
-        let api = builder.build()?;
+        let (api, credential) =
+            authenticated_octocrab(&api_url, &api_url, repository, auth).await?;
 
-        Ok(Self { api })
+        Ok(Self { api, credential })
+    }
+
+    /// Logs in to the Github (Enterprise Server) instance at `host_url` (e.g. `https://github.com/`)
+    /// via the OAuth device flow of the OAuth (or Github) App with the given client ID,
+    /// storing the access token for later `authenticated` calls to pick up.
+    ///
+    /// Returns the path of the file the token got stored in.
+    pub async fn login(host_url: &Url, client_id: &str, scope: &str) -> anyhow::Result<PathBuf> {
+        let token = request_device_token(host_url, client_id, scope).await?;
+
+        store_token(host_url, &token)
     }
 }
 
+/// Creates a client for the Github API at `base_url` (i.e. the REST API at `api_url`, or the GraphQL API),
+/// authenticated as per `auth`, along with the git credentials it got authenticated with, if any.
+///
+/// Github Apps get authenticated as their installation for the repository,
+/// by exchanging a JWT signed with the app's private key for an installation access token.
+async fn authenticated_octocrab(
+    api_url: &Url,
+    base_url: &Url,
+    repository: &GitRepositoryUrl,
+    auth: &AuthOptions,
+) -> anyhow::Result<(Octocrab, Option<GitCredential>)> {
+    let (authentication, credential) = pick_authentication(&repository.host_url()?, auth).await?;
+
+    let GithubAuthentication::App(app) = authentication else {
+        return Ok((octocrab(base_url, authentication)?, credential));
+    };
+
+    let app_api = octocrab(api_url, GithubAuthentication::App(app))?;
+
+    let installation_id = match auth.installation_id {
+        Some(installation_id) => installation_id,
+        None => find_installation_id(&app_api, repository).await?,
+    };
+
+    eprintln!("Authenticating as installation {installation_id} of Github App ...");
+
+    let (api, token) = app_api
+        .installation_and_token(InstallationId(installation_id))
+        .await
+        .map_err(|err| {
+            anyhow::Error::from(err).context(format!(
+                "Could not request access token of installation {installation_id}"
+            ))
+        })?;
+
+    // Installation clients request (and refresh) their access tokens relative to their base url,
+    // which only works for the REST API:
+    if base_url == api_url {
+        return Ok((api, None));
+    }
+
+    let token = GithubAuthentication::UserAccessToken(token);
+
+    Ok((octocrab(base_url, token)?, None))
+}
+
+/// Requests the ID of the Github App's installation that has access to the repository.
+async fn find_installation_id(
+    app_api: &Octocrab,
+    repository: &GitRepositoryUrl,
+) -> anyhow::Result<u64> {
+    let repository = GithubRepository::try_from(repository.clone())?;
+
+    let route = format!(
+        "/repos/{owner}/{name}/installation",
+        owner = repository.owner()?,
+        name = repository.name()?
+    );
+
+    let installation: Installation = app_api.get(route, None::<&()>).await.map_err(|err| {
+        anyhow::Error::from(err).context(format!(
+            "Could not find installation of Github App for {url}, pass `--installation-id <ID>`",
+            url = repository.0.url_string
+        ))
+    })?;
+
+    Ok(installation.id)
+}
+
+/// A Github App's installation.
+#[derive(Debug, Deserialize)]
+struct Installation {
+    id: u64,
+}
+
+/// Creates a client for the Github API at `base_url`, with the given authentication.
+fn octocrab(base_url: &Url, authentication: GithubAuthentication) -> anyhow::Result<Octocrab> {
+    let mut builder = OctocrabBuilder::default().base_uri(base_url.as_str())?;
+
+    builder = match authentication {
+        GithubAuthentication::None => builder,
+        GithubAuthentication::Basic { username, password } => {
+            builder.basic_auth(username, password)
+        }
+        GithubAuthentication::PersonalToken(token) => {
+            builder.personal_token(token.expose_secret().to_owned())
+        }
+        GithubAuthentication::App(AppAuth { app_id, key }) => builder.app(app_id, key),
+        GithubAuthentication::OAuth(oauth) => builder.oauth(oauth),
+        GithubAuthentication::UserAccessToken(token) => {
+            builder.user_access_token(token.expose_secret().to_owned())
+        }
+    };
+
+    Ok(builder.build()?)
+}
+
 #[derive(Clone, Debug)]
 pub struct GithubRepository(GitRepositoryUrl);
 
@@ -52,14 +184,10 @@ impl TryFrom<GitRepositoryUrl> for GithubRepository {
 
     fn try_from(repository: GitRepositoryUrl) -> Result<Self, anyhow::Error> {
         let url = &repository.url_string;
-        match repository.parsed_url.host() {
-            Some(GITHUB_HOST) => {}
-            Some(_) => {
-                anyhow::bail!("Not a Github url: {url}");
-            }
-            None => {
-                anyhow::bail!("No host found in url: {url}");
-            }
+
+        // Github Enterprise Server instances may have arbitrary hostnames:
+        if repository.parsed_url.host().is_none() {
+            anyhow::bail!("No host found in url: {url}");
         }
 
         Ok(Self(repository))
@@ -68,81 +196,230 @@ impl TryFrom<GitRepositoryUrl> for GithubRepository {
 
 impl GithubRepository {
     pub fn owner(&self) -> Result<String, anyhow::Error> {
-        let provider: GenericProvider = self.0.parsed_url.provider_info()?;
-        Ok(provider.owner().clone())
+        let coordinates = self.0.coordinates()?;
+
+        match &coordinates.namespace[..] {
+            [owner] => Ok(owner.clone()),
+            _ => anyhow::bail!("Not a Github repository url: {}", self.0.url_string),
+        }
     }
 
     pub fn name(&self) -> Result<String, anyhow::Error> {
-        let provider: GenericProvider = self.0.parsed_url.provider_info()?;
-        Ok(provider.repo().clone())
+        Ok(self.0.coordinates()?.name)
+    }
+
+    /// Returns the url of the REST API of the Github (Enterprise Server) instance hosting the repository.
+    pub fn api_url(&self) -> anyhow::Result<Url> {
+        let url = &self.0.parsed_url;
+
+        let host = url
+            .host()
+            .ok_or_else(|| anyhow::anyhow!("No host found in url: {url}"))?;
+
+        if host == GITHUB_HOST {
+            return Ok(Url::parse(GITHUB_API_URL)?);
+        }
+
+        let (scheme, port) = match url.scheme() {
+            Some(scheme @ ("http" | "https")) => (scheme, url.port()),
+            _ => ("https", None),
+        };
+
+        let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+        Ok(Url::parse(&format!("{scheme}://{host}{port}/api/v3/"))?)
     }
 }
 
-#[async_trait]
 impl GitHost for GithubApi {
-    async fn merged_pull_requests(
-        &self,
-        repository: &GitRepositoryUrl,
-    ) -> Result<Vec<GitPullRequest>, anyhow::Error> {
-        let safe_repository = GithubRepository::try_from(repository.clone())?;
-
-        let pull_requests = self
-            .api
-            .all_pages(
-                self.api
-                    .pulls(safe_repository.owner()?, safe_repository.name()?)
-                    .list()
-                    .state(State::Closed)
-                    .sort(Sort::Created)
-                    .direction(Direction::Ascending)
-                    .per_page(100)
-                    .send()
-                    .await?,
-            )
-            .await?;
-
-        let pull_requests: Vec<GitPullRequest> = pull_requests
-            .into_iter()
-            .filter(|pull_request| pull_request.merged_at.is_some())
-            .map(|pull_request| {
-                let identifier = pull_request.number.to_string();
-                let title = pull_request.title;
-                let base_sha = pull_request.base.sha;
-                let Some(merge_sha) = pull_request.merge_commit_sha else {
-                    anyhow::bail!("Could not find merge commit sha");
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let first_page_uri = match GithubRepository::try_from(repository.clone())
+            .and_then(|safe_repository| pull_requests_uri(&safe_repository, query))
+        {
+            Ok(first_page_uri) => first_page_uri,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        // Pages get requested one by one, so that a failing request gets retried
+        // (or resumed from) without losing any of the pages requested before:
+        let pages = stream::try_unfold(
+            (Some(first_page_uri), None),
+            move |(page_uri, exhausted_until)| async move {
+                let Some(page_uri) = page_uri else {
+                    return Ok::<_, anyhow::Error>(None);
                 };
 
-                Ok(GitPullRequest {
-                    identifier,
-                    title,
-                    base_sha,
-                    merge_sha,
-                })
+                // Waiting for an exhausted rate limit to reset spares the page's request from failing,
+                // while pages that don't get requested at all don't have to wait for it:
+                if let Some(reset) = exhausted_until {
+                    wait_for_reset(reset).await;
+                }
+
+                let (page, exhausted_until): (Page<PullRequest>, _) =
+                    get_page(&self.api, &page_uri, self.credential.as_ref()).await?;
+
+                // Requesting the next page by its path keeps it relative to the API's base url,
+                // which the client only sends its credentials to:
+                let next_page_uri = page
+                    .next
+                    .as_ref()
+                    .and_then(|uri| uri.path_and_query())
+                    .map(|path_and_query| path_and_query.to_string());
+
+                Ok(Some((page.items, (next_page_uri, exhausted_until))))
+            },
+        );
+
+        pages
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            .take_while(move |result| {
+                let is_too_old = match result {
+                    Ok(pull_request) => match (pull_request.updated_at, query.merged_after) {
+                        (Some(updated_at), Some(merged_after)) => updated_at < merged_after,
+                        _ => false,
+                    },
+                    Err(_) => false,
+                };
+
+                future::ready(!is_too_old)
             })
-            .collect::<Result<_, _>>()?;
+            .try_filter(move |pull_request| {
+                let is_merged = pull_request
+                    .merged_at
+                    .is_some_and(|merged_at| query.contains_merged_at(merged_at));
 
-        Ok(pull_requests)
+                future::ready(is_merged)
+            })
+            .and_then(|pull_request| future::ready(git_pull_request(pull_request)))
+            .boxed()
     }
 }
 
-fn pick_authentication() -> anyhow::Result<GithubAuthentication> {
-    // Check for GITHUB_TOKEN environment variable first
-    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
-        if !token.is_empty() {
-            println!("Using GITHUB_TOKEN from environment");
-            return Ok(GithubAuthentication::PersonalToken(SecretString::from(
-                token,
-            )));
+/// Returns the path of the first page of the repository's closed pull requests matching the query,
+/// relative to the API's base url.
+fn pull_requests_uri(
+    repository: &GithubRepository,
+    query: &PullRequestQuery,
+) -> anyhow::Result<String> {
+    let mut parameters = form_urlencoded::Serializer::new(String::new());
+
+    // Sorting by last update allows for stopping early, as a pull request
+    // can't have been updated any earlier than it got merged:
+    parameters
+        .append_pair("state", "closed")
+        .append_pair("sort", "updated")
+        .append_pair("direction", "desc")
+        .append_pair("per_page", "100");
+
+    if let Some(base_ref) = &query.base_ref {
+        parameters.append_pair("base", base_ref);
+    }
+
+    Ok(format!(
+        "/repos/{owner}/{name}/pulls?{parameters}",
+        owner = repository.owner()?,
+        name = repository.name()?,
+        parameters = parameters.finish()
+    ))
+}
+
+fn git_pull_request(pull_request: PullRequest) -> anyhow::Result<GitPullRequest> {
+    let identifier = pull_request.number.to_string();
+    let title = pull_request.title;
+    let author = pull_request.user.map(|user| user.login);
+    let labels = pull_request
+        .labels
+        .unwrap_or_default()
+        .into_iter()
+        .map(|label| label.name)
+        .collect();
+    let base_ref = pull_request.base.ref_field;
+    let base_sha = Some(pull_request.base.sha);
+    let Some(merge_sha) = pull_request.merge_commit_sha else {
+        anyhow::bail!("Could not find merge commit sha");
+    };
+    let merged_at = pull_request.merged_at;
+
+    Ok(GitPullRequest {
+        identifier,
+        title,
+        author,
+        labels,
+        base_ref,
+        base_sha,
+        merge_sha,
+        merged_at,
+    })
+}
+
+/// Picks the authentication for the Github instance at `host_url` as per `auth`,
+/// along with the git credentials it got resolved from (or is to be stored as), if any.
+///
+/// Tokens get resolved from (in order) the token file, `GH_TOKEN`/`GITHUB_TOKEN`, `git assist auth login`,
+/// the Github CLI, the git credential helpers, and finally prompts, unless running non-interactively.
+async fn pick_authentication(
+    host_url: &Url,
+    auth: &AuthOptions,
+) -> anyhow::Result<(GithubAuthentication, Option<GitCredential>)> {
+    match auth.method() {
+        Some(AuthMethod::None) => return Ok((request_no_auth()?, None)),
+        Some(AuthMethod::App) => return Ok((request_app_auth(auth)?, None)),
+        Some(AuthMethod::Token) | None => {}
+    }
+
+    if let Some(token) = auth.token(GITHUB_TOKEN_VARS)? {
+        return Ok((GithubAuthentication::PersonalToken(token), None));
+    }
+
+    if let Some(token) = stored_token(host_url)? {
+        return Ok((GithubAuthentication::UserAccessToken(token), None));
+    }
+
+    if let Some(hostname) = host_url.host_str() {
+        if let Some(token) = gh_token(hostname)? {
+            return Ok((GithubAuthentication::PersonalToken(token), None));
         }
     }
 
+    if let Some(credential) = auth.stored_credential(host_url)? {
+        let token = credential.password().clone();
+        return Ok((GithubAuthentication::PersonalToken(token), Some(credential)));
+    }
+
+    auth.ensure_interactive(GITHUB_TOKEN_VARS)?;
+
+    let authentication = match auth.method() {
+        Some(AuthMethod::Token) => request_personal_token()?,
+        _ => select_authentication(host_url, auth).await?,
+    };
+
+    let credential = match &authentication {
+        GithubAuthentication::PersonalToken(token)
+        | GithubAuthentication::UserAccessToken(token) => {
+            auth.offer_to_store(host_url, TOKEN_USERNAME, token)?
+        }
+        _ => None,
+    };
+
+    Ok((authentication, credential))
+}
+
+async fn select_authentication(
+    host_url: &Url,
+    auth: &AuthOptions,
+) -> anyhow::Result<GithubAuthentication> {
     enum AuthKind {
         PersonalToken,
         None,
         UserAccessToken,
         Basic,
         App,
-        OAuth,
+        DeviceFlow,
     }
 
     let auth_kinds = [
@@ -151,7 +428,7 @@ fn pick_authentication() -> anyhow::Result<GithubAuthentication> {
         AuthKind::UserAccessToken,
         AuthKind::Basic,
         AuthKind::App,
-        AuthKind::OAuth,
+        AuthKind::DeviceFlow,
     ];
 
     let auth_labels: Vec<_> = auth_kinds
@@ -160,10 +437,10 @@ fn pick_authentication() -> anyhow::Result<GithubAuthentication> {
             AuthKind::None => "No authentication (public API only, rate limited)",
             AuthKind::Basic => "Basic authentication - username:password (deprecated by GitHub)",
             AuthKind::PersonalToken => {
-                "Personal access token (recommended - set GITHUB_TOKEN env var to skip this prompt)"
+                "Personal access token (recommended - set GH_TOKEN or GITHUB_TOKEN env var to skip this prompt)"
             }
             AuthKind::App => "GitHub App authentication (for app developers)",
-            AuthKind::OAuth => "OAuth token (for OAuth apps)",
+            AuthKind::DeviceFlow => "Log in via browser (requires the client ID of an OAuth app)",
             AuthKind::UserAccessToken => "User access token (fine-grained PAT)",
         })
         .collect();
@@ -180,8 +457,8 @@ fn pick_authentication() -> anyhow::Result<GithubAuthentication> {
         AuthKind::None => request_no_auth(),
         AuthKind::Basic => request_basic_auth(),
         AuthKind::PersonalToken => request_personal_token(),
-        AuthKind::App => request_app_auth(),
-        AuthKind::OAuth => request_oauth(),
+        AuthKind::App => request_app_auth(auth),
+        AuthKind::DeviceFlow => request_device_authorization(host_url).await,
         AuthKind::UserAccessToken => request_user_access_token(),
     }
 }
@@ -217,55 +494,48 @@ fn request_personal_token() -> anyhow::Result<GithubAuthentication> {
     Ok(GithubAuthentication::PersonalToken(personal_token))
 }
 
-fn request_app_auth() -> anyhow::Result<GithubAuthentication> {
-    let app_id = {
-        let string = Password::new("App ID:")
-            .with_display_toggle_enabled()
-            .with_display_mode(PasswordDisplayMode::Hidden)
-            .prompt()?;
+fn request_app_auth(auth: &AuthOptions) -> anyhow::Result<GithubAuthentication> {
+    if (auth.app_id.is_none() || auth.app_key_file.is_none()) && !auth.is_interactive() {
+        anyhow::bail!(
+            "Github App authentication requires `--app-id <ID>` and `--app-key-file <PATH>` when not prompting for them"
+        );
+    }
 
-        u64::from_str(&string).map(AppId::from)
-    }?;
+    let app_id = match auth.app_id {
+        Some(app_id) => app_id,
+        None => u64::from_str(&Text::new("App ID:").prompt()?)?,
+    };
 
-    let key = {
-        let string = Password::new("Encoding key:")
-            .with_display_toggle_enabled()
-            .with_display_mode(PasswordDisplayMode::Hidden)
-            .prompt()?;
+    let key_file = match &auth.app_key_file {
+        Some(key_file) => key_file.clone(),
+        None => {
+            let path = Text::new("Private key file (PEM):").prompt()?;
+            PathBuf::from(shellexpand::tilde(&path).as_ref())
+        }
+    };
 
-        EncodingKey::from_base64_secret(&string)
-    }?;
+    let key = fs::read(&key_file)
+        .map_err(anyhow::Error::from)
+        .and_then(|pem| Ok(EncodingKey::from_rsa_pem(&pem)?))
+        .map_err(|err| {
+            anyhow::anyhow!(
+                "Could not read Github App private key {path}: {err}",
+                path = key_file.display()
+            )
+        })?;
 
-    Ok(GithubAuthentication::App(AppAuth { app_id, key }))
+    Ok(GithubAuthentication::App(AppAuth {
+        app_id: AppId::from(app_id),
+        key,
+    }))
 }
 
-fn request_oauth() -> anyhow::Result<GithubAuthentication> {
-    let access_token = {
-        let string = Password::new("Access token:")
-            .with_display_toggle_enabled()
-            .with_display_mode(PasswordDisplayMode::Hidden)
-            .prompt()
-            .map_err(anyhow::Error::from)?;
+async fn request_device_authorization(host_url: &Url) -> anyhow::Result<GithubAuthentication> {
+    let client_id = Text::new("OAuth App client ID:").prompt()?;
 
-        SecretString::from(string)
-    };
-
-    let token_type = Text::new("Token type:").prompt()?;
-
-    let scope = {
-        let string = Text::new("Scope (comma-separated list):").prompt()?;
+    let token = request_device_token(host_url, &client_id, DEFAULT_OAUTH_SCOPE).await?;
 
-        string.split(',').map(|s| s.to_owned()).collect::<Vec<_>>()
-    };
-
-    Ok(GithubAuthentication::OAuth(octocrab::auth::OAuth {
-        access_token,
-        expires_in: None,
-        refresh_token_expires_in: None,
-        refresh_token: None,
-        scope,
-        token_type,
-    }))
+    Ok(GithubAuthentication::UserAccessToken(token))
 }
 
 // Note: Fine-grained personal access tokens may need to be
@@ -0,0 +1,111 @@
+use std::{collections::HashMap, fs, path::PathBuf, process::Command};
+
+use secrecy::SecretString;
+use serde::Deserialize;
+
+/// The Github CLI's per-host configuration, as written by `gh auth login`.
+#[derive(Debug, Deserialize)]
+struct GhHostConfig {
+    /// The active user's token, unless kept in the system's keyring.
+    oauth_token: Option<String>,
+
+    /// The active user.
+    user: Option<String>,
+
+    /// The logged-in users, with their tokens, unless kept in the system's keyring.
+    #[serde(default)]
+    users: HashMap<String, Option<GhUserConfig>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GhUserConfig {
+    oauth_token: Option<String>,
+}
+
+/// Resolves the token the Github CLI is logged in with for the host, read from its `hosts.yml` config file,
+/// or (if it keeps the token in the system's keyring instead) via `gh auth token`.
+///
+/// Returns `None` if the Github CLI isn't logged in for the host.
+pub(super) fn gh_token(hostname: &str) -> anyhow::Result<Option<SecretString>> {
+    let Some(path) = gh_config_directory().map(|directory| directory.join("hosts.yml")) else {
+        return Ok(None);
+    };
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let yaml = fs::read_to_string(&path)?;
+
+    let mut hosts: HashMap<String, GhHostConfig> = match serde_yaml_ng::from_str(&yaml) {
+        Ok(hosts) => hosts,
+        Err(err) => {
+            eprintln!(
+                "Warning: Ignoring invalid Github CLI config {path}: {err}",
+                path = path.display()
+            );
+            return Ok(None);
+        }
+    };
+
+    let Some(mut host) = hosts.remove(hostname) else {
+        return Ok(None);
+    };
+
+    let active_user_token = host
+        .user
+        .as_ref()
+        .and_then(|user| host.users.remove(user))
+        .flatten()
+        .and_then(|user| user.oauth_token);
+
+    let token = match host.oauth_token.or(active_user_token) {
+        Some(token) => Some(token),
+        None => gh_auth_token(hostname),
+    };
+
+    let Some(token) = token.filter(|token| !token.is_empty()) else {
+        return Ok(None);
+    };
+
+    eprintln!("Using token of Github CLI for {hostname}");
+
+    Ok(Some(SecretString::from(token)))
+}
+
+/// Returns the Github CLI's config directory, as per `gh help environment`.
+fn gh_config_directory() -> Option<PathBuf> {
+    if let Some(directory) = std::env::var_os("GH_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(directory));
+    }
+
+    if let Some(directory) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(directory).join("gh"));
+    }
+
+    if cfg!(windows) {
+        if let Some(directory) = std::env::var_os("AppData").filter(|dir| !dir.is_empty()) {
+            return Some(PathBuf::from(directory).join("GitHub CLI"));
+        }
+    }
+
+    let directory = shellexpand::tilde("~/.config/gh");
+
+    (directory != "~/.config/gh").then(|| PathBuf::from(directory.as_ref()))
+}
+
+/// Requests the token kept in the system's keyring from the Github CLI (if installed).
+fn gh_auth_token(hostname: &str) -> Option<String> {
+    let output = Command::new("gh")
+        .args(["auth", "token", "--hostname", hostname])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let token = String::from_utf8(output.stdout).ok()?;
+
+    Some(token.trim().to_owned())
+}
@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use http::header::ACCEPT;
+use octocrab::{Octocrab, OctocrabBuilder};
+use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use url::Url;
+
+/// The grant type of the access token requests polling for the user's authorization.
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// The delay added to the polling interval whenever the host asks to slow down.
+const SLOW_DOWN_INCREMENT: Duration = Duration::from_secs(5);
+
+/// The codes identifying a pending device authorization, along with the url to authorize it at.
+#[derive(Debug, Deserialize)]
+struct DeviceCodes {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    expires_in: u64,
+    interval: u64,
+}
+
+/// An error of the OAuth endpoints, which report them with a `200 OK` status.
+#[derive(Debug, Deserialize)]
+struct OAuthError {
+    error: String,
+    error_description: Option<String>,
+
+    /// The polling interval to use from now on (if the host asked to slow down).
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DeviceCodesResponse {
+    Codes(DeviceCodes),
+    Error(OAuthError),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AccessTokenResponse {
+    Token { access_token: String },
+    Error(OAuthError),
+}
+
+#[derive(Debug, Serialize)]
+struct DeviceCodesRequest<'a> {
+    client_id: &'a str,
+    scope: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct AccessTokenRequest<'a> {
+    client_id: &'a str,
+    device_code: &'a str,
+    grant_type: &'a str,
+}
+
+/// Requests an access token for the OAuth (or Github) App with the given client ID
+/// from the Github instance at `host_url` (e.g. `https://github.com/`), via the OAuth device flow:
+/// shows the user a code to enter at the host's verification url,
+/// then polls the host until the user authorized the app (or denied it, or the code expired).
+pub(super) async fn request_device_token(
+    host_url: &Url,
+    client_id: &str,
+    scope: &str,
+) -> anyhow::Result<SecretString> {
+    let api = oauth_client(host_url)?;
+
+    let codes = request_device_codes(&api, client_id, scope)
+        .await
+        .map_err(|err| err.context(format!("Could not request device code from {host_url}")))?;
+
+    eprintln!(
+        "First copy your one-time code: {user_code}",
+        user_code = codes.user_code
+    );
+    eprintln!(
+        "Then open {verification_uri} in your browser and enter the code to authorize the app",
+        verification_uri = codes.verification_uri
+    );
+
+    poll_access_token(&api, client_id, &codes).await
+}
+
+/// Returns a client for the OAuth endpoints of the Github instance at `host_url`.
+fn oauth_client(host_url: &Url) -> anyhow::Result<Octocrab> {
+    // The OAuth endpoints respond with form-encoded bodies, unless asked for JSON:
+    Ok(OctocrabBuilder::default()
+        .base_uri(host_url.as_str())?
+        .add_header(ACCEPT, "application/json".to_owned())
+        .build()?)
+}
+
+async fn request_device_codes(
+    api: &Octocrab,
+    client_id: &str,
+    scope: &str,
+) -> anyhow::Result<DeviceCodes> {
+    let request = DeviceCodesRequest { client_id, scope };
+
+    match api.post("/login/device/code", Some(&request)).await? {
+        DeviceCodesResponse::Codes(codes) => Ok(codes),
+        DeviceCodesResponse::Error(err) => Err(oauth_error(err)),
+    }
+}
+
+/// Polls for the device authorization's access token, at the interval the host asks for,
+/// until the user authorized the app (or denied it, or the code expired).
+///
+/// Once the code expired, the host gets polled one last time, in case the user authorized the app in the meantime.
+async fn poll_access_token(
+    api: &Octocrab,
+    client_id: &str,
+    codes: &DeviceCodes,
+) -> anyhow::Result<SecretString> {
+    let request = AccessTokenRequest {
+        client_id,
+        device_code: &codes.device_code,
+        grant_type: DEVICE_CODE_GRANT_TYPE,
+    };
+
+    let expires_at = Instant::now() + Duration::from_secs(codes.expires_in);
+    let mut interval = Duration::from_secs(codes.interval);
+
+    eprintln!("Waiting for authorization ...");
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let is_expired = Instant::now() >= expires_at;
+
+        let err = match api
+            .post("/login/oauth/access_token", Some(&request))
+            .await?
+        {
+            AccessTokenResponse::Token { access_token } => {
+                return Ok(SecretString::from(access_token));
+            }
+            AccessTokenResponse::Error(err) => err,
+        };
+
+        match err.error.as_str() {
+            "authorization_pending" | "slow_down" if is_expired => {
+                anyhow::bail!("Device code expired before the app got authorized, please try again")
+            }
+            "authorization_pending" => {}
+            "slow_down" => {
+                interval = match err.interval {
+                    Some(seconds) => Duration::from_secs(seconds),
+                    None => interval + SLOW_DOWN_INCREMENT,
+                };
+            }
+            "expired_token" => {
+                anyhow::bail!("Device code expired before the app got authorized, please try again")
+            }
+            "access_denied" => anyhow::bail!("Authorization of the app got denied"),
+            _ => return Err(oauth_error(err)),
+        }
+    }
+}
+
+fn oauth_error(err: OAuthError) -> anyhow::Error {
+    match err.error_description {
+        Some(description) => anyhow::anyhow!("{description} ({error})", error = err.error),
+        None => anyhow::anyhow!("OAuth request failed: {error}", error = err.error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use secrecy::ExposeSecret;
+    use serde_json::json;
+
+    use crate::test_support::{TestResponse, TestServer};
+
+    use super::*;
+
+    /// Starts a server answering the access token requests with the given responses, in order.
+    fn start_server(responses: Vec<serde_json::Value>) -> TestServer {
+        let responses = Arc::new(Mutex::new(responses.into_iter()));
+
+        TestServer::start(move |request| {
+            assert_eq!(request.path, "/login/oauth/access_token");
+
+            let response = responses
+                .lock()
+                .unwrap()
+                .next()
+                .expect("Unexpected request");
+            TestResponse::json(200, response)
+        })
+    }
+
+    fn codes(expires_in: u64, interval: u64) -> DeviceCodes {
+        DeviceCodes {
+            device_code: "device-code".to_owned(),
+            user_code: "ABCD-1234".to_owned(),
+            verification_uri: "https://github.com/login/device".to_owned(),
+            expires_in,
+            interval,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_access_token_polls_until_authorized() {
+        let server = start_server(vec![
+            json!({ "error": "authorization_pending" }),
+            json!({ "error": "slow_down" }),
+            json!({ "error": "slow_down", "interval": 20 }),
+            json!({ "access_token": "token" }),
+        ]);
+        let api = oauth_client(server.url()).unwrap();
+
+        let started_at = Instant::now();
+        let token = poll_access_token(&api, "client-id", &codes(900, 5))
+            .await
+            .unwrap();
+
+        assert_eq!(token.expose_secret(), "token");
+
+        // Waits 5s, then 5s, then 5s + 5s after slowing down, then the 20s asked for:
+        assert_eq!(started_at.elapsed(), Duration::from_secs(40));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+
+        let body: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(body["client_id"], "client-id");
+        assert_eq!(body["device_code"], "device-code");
+        assert_eq!(body["grant_type"], DEVICE_CODE_GRANT_TYPE);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_access_token_polls_once_more_after_expiry() {
+        let server = start_server(vec![
+            json!({ "error": "authorization_pending" }),
+            json!({ "access_token": "token" }),
+        ]);
+        let api = oauth_client(server.url()).unwrap();
+
+        let token = poll_access_token(&api, "client-id", &codes(10, 5))
+            .await
+            .unwrap();
+
+        assert_eq!(token.expose_secret(), "token");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_access_token_fails_once_expired() {
+        let server = start_server(vec![
+            json!({ "error": "authorization_pending" }),
+            json!({ "error": "authorization_pending" }),
+        ]);
+        let api = oauth_client(server.url()).unwrap();
+
+        let err = poll_access_token(&api, "client-id", &codes(10, 5))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("expired"), "{err}");
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_access_token_fails_on_expired_token() {
+        let server = start_server(vec![json!({ "error": "expired_token" })]);
+        let api = oauth_client(server.url()).unwrap();
+
+        let err = poll_access_token(&api, "client-id", &codes(900, 5))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("expired"), "{err}");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_access_token_fails_on_access_denied() {
+        let server = start_server(vec![json!({ "error": "access_denied" })]);
+        let api = oauth_client(server.url()).unwrap();
+
+        let err = poll_access_token(&api, "client-id", &codes(900, 5))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("denied"), "{err}");
+    }
+}
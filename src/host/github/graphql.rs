@@ -0,0 +1,438 @@
+use chrono::{DateTime, Utc};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::host::{
+    credentials::GitCredential, AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl,
+    PullRequestQuery, PullRequestStream,
+};
+
+use super::{
+    authenticated_octocrab,
+    retry::{post_graphql, wait_for_reset},
+    GithubRepository,
+};
+
+/// The maximum number of labels requested per pull request (the most the API allows for).
+const MAX_LABELS: usize = 100;
+
+/// Only the fields that are required for classifying and filtering pull requests get requested.
+const MERGED_PULL_REQUESTS_QUERY: &str = r#"
+query($owner: String!, $name: String!, $baseRefName: String, $cursor: String) {
+  repository(owner: $owner, name: $name) {
+    pullRequests(
+      states: MERGED,
+      baseRefName: $baseRefName,
+      first: 100,
+      after: $cursor,
+      orderBy: { field: UPDATED_AT, direction: DESC }
+    ) {
+      pageInfo { hasNextPage endCursor }
+      nodes {
+        number
+        title
+        author { login }
+        labels(first: 100) { totalCount nodes { name } }
+        baseRefName
+        baseRefOid
+        mergeCommit { oid }
+        mergedAt
+        updatedAt
+      }
+    }
+  }
+}
+"#;
+
+/// A Github backend requesting pull requests via the GraphQL API,
+/// which keeps responses (and thus rate-limit usage) considerably smaller than the REST API.
+#[derive(Clone, Default, Debug)]
+pub struct GithubGraphqlApi {
+    api: Octocrab,
+    credential: Option<GitCredential>,
+}
+
+impl GithubGraphqlApi {
+    /// Creates a client for the Github (Enterprise Server) instance hosting the repository,
+    /// or at the REST API's `api_url`, if provided, authenticated as per `auth`.
+    pub async fn authenticated(
+        repository: &GitRepositoryUrl,
+        api_url: Option<Url>,
+        auth: &AuthOptions,
+    ) -> anyhow::Result<Self> {
+        let api_url = match api_url {
+            Some(api_url) => api_url,
+            None => GithubRepository::try_from(repository.clone())?.api_url()?,
+        };
+
+        let graphql_url = graphql_base_url(api_url.clone());
+
+        let (api, credential) =
+            authenticated_octocrab(&api_url, &graphql_url, repository, auth).await?;
+
+        Ok(Self { api, credential })
+    }
+
+    /// Requests a page of pull requests, returning them along with the point in time
+    /// (as a Unix timestamp) the rate limit resets at, if the request exhausted it.
+    async fn pull_requests_page(
+        &self,
+        repository: &GithubRepository,
+        query: &PullRequestQuery,
+        cursor: Option<String>,
+    ) -> anyhow::Result<(PullRequestConnection, Option<u64>)> {
+        let payload = Payload {
+            query: MERGED_PULL_REQUESTS_QUERY,
+            variables: Variables {
+                owner: repository.owner()?,
+                name: repository.name()?,
+                base_ref_name: query.base_ref.clone(),
+                cursor,
+            },
+        };
+
+        let (response, exhausted_until) = loop {
+            let (response, exhausted_until): (Response, _) =
+                post_graphql(&self.api, &payload, self.credential.as_ref()).await?;
+
+            // Exceeding the rate limit gets reported as an error of an otherwise successful response:
+            let is_rate_limited = response
+                .errors
+                .iter()
+                .any(|error| error.kind.as_deref() == Some("RATE_LIMITED"));
+
+            match exhausted_until {
+                Some(reset) if is_rate_limited => wait_for_reset(reset).await,
+                _ => break (response, exhausted_until),
+            }
+        };
+
+        if let Some(error) = response.errors.first() {
+            anyhow::bail!("Github GraphQL error: {message}", message = error.message);
+        }
+
+        let repository = response
+            .data
+            .and_then(|data| data.repository)
+            .ok_or_else(|| anyhow::anyhow!("Could not find repository"))?;
+
+        Ok((repository.pull_requests, exhausted_until))
+    }
+}
+
+/// Returns the base url for Github's GraphQL API (i.e. `…/api/graphql` on Github Enterprise Server),
+/// derived from the url of its REST API (i.e. `…/api/v3/`).
+fn graphql_base_url(mut api_url: Url) -> Url {
+    let is_versioned = api_url
+        .path_segments()
+        .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+        .is_some_and(|segment| segment == "v3");
+
+    if is_versioned {
+        if let Ok(mut segments) = api_url.path_segments_mut() {
+            segments.pop_if_empty().pop();
+        }
+    }
+
+    api_url
+}
+
+impl GitHost for GithubGraphqlApi {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let safe_repository = match GithubRepository::try_from(repository.clone()) {
+            Ok(safe_repository) => safe_repository,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        // The outer `Option` marks the end of pagination, the inner one the lack of a cursor:
+        let pages = stream::try_unfold((Some(None), None), move |(cursor, exhausted_until)| {
+            let safe_repository = safe_repository.clone();
+            async move {
+                let Some(cursor) = cursor else {
+                    return Ok::<_, anyhow::Error>(None);
+                };
+
+                // Waiting for an exhausted rate limit to reset spares the page's request from failing,
+                // while pages that don't get requested at all don't have to wait for it:
+                if let Some(reset) = exhausted_until {
+                    wait_for_reset(reset).await;
+                }
+
+                let (connection, exhausted_until) = self
+                    .pull_requests_page(&safe_repository, query, cursor)
+                    .await?;
+
+                let next_cursor = match connection.page_info {
+                    PageInfo {
+                        has_next_page: true,
+                        end_cursor: Some(end_cursor),
+                    } => Some(Some(end_cursor)),
+                    _ => None,
+                };
+
+                Ok(Some((connection.nodes, (next_cursor, exhausted_until))))
+            }
+        });
+
+        pages
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            // Sorting by last update allows for stopping early, as a pull request
+            // can't have been updated any earlier than it got merged:
+            .take_while(move |result| {
+                let is_too_old = match (result, query.merged_after) {
+                    (Ok(pull_request), Some(merged_after)) => {
+                        pull_request.updated_at < merged_after
+                    }
+                    _ => false,
+                };
+
+                future::ready(!is_too_old)
+            })
+            .try_filter(move |pull_request| {
+                let is_merged = pull_request
+                    .merged_at
+                    .is_some_and(|merged_at| query.contains_merged_at(merged_at));
+
+                future::ready(is_merged)
+            })
+            .and_then(|pull_request| future::ready(git_pull_request(pull_request)))
+            .boxed()
+    }
+}
+
+fn git_pull_request(pull_request: PullRequest) -> anyhow::Result<GitPullRequest> {
+    let Some(merge_commit) = pull_request.merge_commit else {
+        anyhow::bail!("Could not find merge commit sha");
+    };
+
+    if let Some(labels) = &pull_request.labels {
+        if labels.total_count > labels.nodes.len() {
+            eprintln!(
+                "Warning: Pull request #{number} has {total_count} labels, only its first {MAX_LABELS} get considered by filters",
+                number = pull_request.number,
+                total_count = labels.total_count
+            );
+        }
+    }
+
+    Ok(GitPullRequest {
+        identifier: pull_request.number.to_string(),
+        title: Some(pull_request.title),
+        author: pull_request.author.map(|author| author.login),
+        labels: pull_request
+            .labels
+            .map(|labels| labels.nodes.into_iter().map(|label| label.name).collect())
+            .unwrap_or_default(),
+        base_ref: pull_request.base_ref_name,
+        base_sha: Some(pull_request.base_ref_oid),
+        merge_sha: merge_commit.oid,
+        merged_at: pull_request.merged_at,
+    })
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Payload {
+    query: &'static str,
+    variables: Variables,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Variables {
+    owner: String,
+    name: String,
+    base_ref_name: Option<String>,
+    cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Response {
+    data: Option<ResponseData>,
+    #[serde(default)]
+    errors: Vec<ResponseError>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ResponseData {
+    repository: Option<Repository>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ResponseError {
+    message: String,
+
+    /// The error's type (e.g. `RATE_LIMITED`), if any.
+    #[serde(rename = "type")]
+    kind: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Repository {
+    pull_requests: PullRequestConnection,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestConnection {
+    page_info: PageInfo,
+    nodes: Vec<PullRequest>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequest {
+    number: u64,
+    title: String,
+    author: Option<Actor>,
+    labels: Option<LabelConnection>,
+    base_ref_name: String,
+    base_ref_oid: String,
+    merge_commit: Option<Commit>,
+    merged_at: Option<DateTime<Utc>>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Actor {
+    login: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LabelConnection {
+    /// The number of labels, including those beyond the first `MAX_LABELS`.
+    #[serde(default)]
+    total_count: usize,
+    nodes: Vec<Label>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Label {
+    name: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Commit {
+    oid: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        str::FromStr,
+        sync::atomic::{AtomicUsize, Ordering},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use octocrab::OctocrabBuilder;
+    use serde_json::json;
+
+    use crate::test_support::{TestResponse, TestServer};
+
+    use super::*;
+
+    fn pull_requests_response(numbers: &[u64], end_cursor: Option<&str>) -> serde_json::Value {
+        let nodes: Vec<serde_json::Value> = numbers
+            .iter()
+            .map(|number| {
+                json!({
+                    "number": number,
+                    "title": format!("Pull request {number}"),
+                    "author": { "login": "octocat" },
+                    "labels": { "totalCount": 1, "nodes": [{ "name": "bug" }] },
+                    "baseRefName": "main",
+                    "baseRefOid": "b".repeat(40),
+                    "mergeCommit": { "oid": format!("{number:040}") },
+                    "mergedAt": "2026-10-01T00:00:00Z",
+                    "updatedAt": "2026-10-01T00:00:00Z",
+                })
+            })
+            .collect();
+
+        json!({
+            "data": {
+                "repository": {
+                    "pullRequests": {
+                        "pageInfo": { "hasNextPage": end_cursor.is_some(), "endCursor": end_cursor },
+                        "nodes": nodes,
+                    }
+                }
+            }
+        })
+    }
+
+    async fn identifiers(server: &TestServer) -> anyhow::Result<Vec<String>> {
+        let api = GithubGraphqlApi {
+            api: OctocrabBuilder::default()
+                .base_uri(server.url().as_str())?
+                .build()?,
+            credential: None,
+        };
+
+        let repository = GitRepositoryUrl::from_str("https://github.com/owner/name.git")?;
+
+        let pull_requests: Vec<GitPullRequest> = api
+            .merged_pull_requests(&repository, &PullRequestQuery::default())
+            .try_collect()
+            .await?;
+
+        Ok(pull_requests
+            .into_iter()
+            .map(|pull_request| pull_request.identifier)
+            .collect())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn merged_pull_requests_retries_rate_limits_and_server_errors() {
+        let reset = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .to_string();
+
+        let count = AtomicUsize::new(0);
+
+        let server = TestServer::start(move |request| {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/graphql");
+
+            match count.fetch_add(1, Ordering::SeqCst) {
+                0 => TestResponse::json(200, pull_requests_response(&[3, 2], Some("cursor"))),
+                1 => TestResponse::json(502, json!({ "message": "Bad Gateway" })),
+                2 => TestResponse::json(
+                    200,
+                    json!({
+                        "data": null,
+                        "errors": [{ "type": "RATE_LIMITED", "message": "API rate limit exceeded" }],
+                    }),
+                )
+                .header("x-ratelimit-remaining", "0")
+                .header("x-ratelimit-reset", &reset),
+                _ => TestResponse::json(200, pull_requests_response(&[1], None)),
+            }
+        });
+
+        assert_eq!(identifiers(&server).await.unwrap(), ["3", "2", "1"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+
+        let body: serde_json::Value = serde_json::from_str(&requests[3].body).unwrap();
+        assert_eq!(body["variables"]["cursor"], "cursor");
+    }
+}
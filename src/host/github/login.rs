@@ -0,0 +1,139 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// A host's configuration, as written by `git assist auth login`.
+#[derive(Debug, Serialize, Deserialize)]
+struct HostConfig {
+    oauth_token: String,
+}
+
+/// Returns the token stored by `git assist auth login` for the host of `host_url`, if any.
+pub(super) fn stored_token(host_url: &Url) -> anyhow::Result<Option<SecretString>> {
+    let Some(path) = hosts_path() else {
+        return Ok(None);
+    };
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut hosts = match read_hosts(&path) {
+        Ok(hosts) => hosts,
+        Err(err) => {
+            eprintln!(
+                "Warning: Ignoring invalid config {path}: {err}",
+                path = path.display()
+            );
+            return Ok(None);
+        }
+    };
+
+    let host = host_key(host_url)?;
+
+    let Some(config) = hosts.remove(&host) else {
+        return Ok(None);
+    };
+
+    if config.oauth_token.is_empty() {
+        return Ok(None);
+    }
+
+    eprintln!("Using token of `git assist auth login` for {host}");
+
+    Ok(Some(SecretString::from(config.oauth_token)))
+}
+
+/// Stores the token for the host of `host_url`, for `stored_token` to pick up later on,
+/// returning the path of the file it got stored in.
+///
+/// The file is only readable by its owner (on Unix).
+pub(super) fn store_token(host_url: &Url, token: &SecretString) -> anyhow::Result<PathBuf> {
+    let Some(path) = hosts_path() else {
+        anyhow::bail!("Could not find config directory to store token in");
+    };
+
+    let mut hosts = if path.exists() {
+        read_hosts(&path)?
+    } else {
+        BTreeMap::new()
+    };
+
+    let config = HostConfig {
+        oauth_token: token.expose_secret().to_owned(),
+    };
+
+    hosts.insert(host_key(host_url)?, config);
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+
+    let json = serde_json::to_string_pretty(&hosts)?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        // The mode only applies to newly created files:
+        if path.exists() {
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    let mut file = options.open(&path)?;
+    file.write_all((json + "\n").as_bytes())?;
+
+    Ok(path)
+}
+
+fn read_hosts(path: &Path) -> anyhow::Result<BTreeMap<String, HostConfig>> {
+    let json = fs::read_to_string(path)?;
+
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Returns the host (including any port) that tokens get stored by.
+fn host_key(host_url: &Url) -> anyhow::Result<String> {
+    let Some(host) = host_url.host_str() else {
+        anyhow::bail!("No host found in url: {host_url}");
+    };
+
+    Ok(match host_url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_owned(),
+    })
+}
+
+/// Returns the path of the file that tokens get stored in,
+/// within the tool's config directory (i.e. `$XDG_CONFIG_HOME/git-assist`, or `~/.config/git-assist`).
+fn hosts_path() -> Option<PathBuf> {
+    Some(config_directory()?.join("hosts.json"))
+}
+
+fn config_directory() -> Option<PathBuf> {
+    if let Some(directory) = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(directory).join("git-assist"));
+    }
+
+    if cfg!(windows) {
+        if let Some(directory) = std::env::var_os("AppData").filter(|dir| !dir.is_empty()) {
+            return Some(PathBuf::from(directory).join("git-assist"));
+        }
+    }
+
+    let directory = shellexpand::tilde("~/.config/git-assist");
+
+    (directory != "~/.config/git-assist").then(|| PathBuf::from(directory.as_ref()))
+}
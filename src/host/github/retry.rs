@@ -0,0 +1,197 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use octocrab::{map_github_error, FromResponse, Octocrab, Page};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::host::credentials::{review_credential, GitCredential};
+
+/// The maximum number of consecutive retries of a request after transient failures.
+const MAX_RETRIES: u32 = 5;
+
+/// The initial delay before retrying a request after a server error,
+/// doubled with each consecutive retry.
+const SERVER_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The initial delay before retrying a request after hitting a secondary rate limit
+/// without being told how long to wait, doubled with each consecutive retry.
+const SECONDARY_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Requests a page of results from `uri` (i.e. a path, relative to the API's base url),
+/// as per `send_with_retries`.
+pub(super) async fn get_page<T>(
+    api: &Octocrab,
+    uri: &str,
+    credential: Option<&GitCredential>,
+) -> anyhow::Result<(Page<T>, Option<u64>)>
+where
+    T: DeserializeOwned,
+{
+    send_with_retries(api, Request::Get(uri), credential).await
+}
+
+/// Posts a query to the GraphQL API, as per `send_with_retries`.
+pub(super) async fn post_graphql<T>(
+    api: &Octocrab,
+    payload: &impl Serialize,
+    credential: Option<&GitCredential>,
+) -> anyhow::Result<(T, Option<u64>)>
+where
+    T: DeserializeOwned,
+{
+    let body = serde_json::to_value(payload)?;
+
+    send_with_retries(api, Request::Post("/graphql", &body), credential).await
+}
+
+/// A request to be sent (and possibly retried) by `send_with_retries`.
+#[derive(Copy, Clone, Debug)]
+enum Request<'a> {
+    Get(&'a str),
+    Post(&'a str, &'a serde_json::Value),
+}
+
+/// Sends the request to `uri` (i.e. a path, relative to the API's base url),
+/// waiting for exceeded rate limits to reset and retrying transient failures,
+/// while reporting the response's status to the git credentials it's authenticated with, if any.
+///
+/// Once the primary rate limit is exceeded, the request gets retried after it resets,
+/// while secondary rate limits and server errors get retried with exponential backoff.
+///
+/// Returns the response along with the point in time (as a Unix timestamp) the primary rate limit resets at,
+/// if the request exhausted it, for the caller to wait for before sending any further requests.
+async fn send_with_retries<T>(
+    api: &Octocrab,
+    request: Request<'_>,
+    credential: Option<&GitCredential>,
+) -> anyhow::Result<(T, Option<u64>)>
+where
+    T: FromResponse,
+{
+    let mut retries = 0;
+
+    loop {
+        let response = match request {
+            Request::Get(uri) => api._get(uri).await,
+            Request::Post(uri, body) => api._post(uri, Some(body)).await,
+        };
+
+        let response = match response {
+            Ok(response) => response,
+            Err(err @ (octocrab::Error::Hyper { .. } | octocrab::Error::Service { .. })) => {
+                retries += 1;
+                if retries > MAX_RETRIES {
+                    return Err(anyhow::Error::from(err)
+                        .context(format!("Request failed after {MAX_RETRIES} retries")));
+                }
+
+                let delay = backoff(SERVER_ERROR_BACKOFF, retries);
+                count_down("Request failed", delay).await;
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let status = response.status();
+
+        review_credential(credential, status.as_u16());
+
+        let header = |name: &str| -> Option<u64> {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        };
+
+        let remaining = header("x-ratelimit-remaining");
+        let reset = header("x-ratelimit-reset");
+        let retry_after = header("retry-after");
+
+        if status.is_success() {
+            let value = T::from_response(response).await?;
+            let exhausted_until = reset.filter(|_| remaining == Some(0));
+
+            return Ok((value, exhausted_until));
+        }
+
+        let is_rate_limited = status.as_u16() == 403 || status.as_u16() == 429;
+        let is_server_error = status.is_server_error();
+        let mut is_secondary_rate_limited = status.as_u16() == 429;
+
+        let err = match map_github_error(response).await {
+            Ok(_) => anyhow::anyhow!("Request failed with status {status}"),
+            Err(err) => {
+                is_secondary_rate_limited |= matches!(
+                    &err,
+                    octocrab::Error::GitHub { source, .. }
+                        if source.message.contains("secondary rate limit")
+                );
+
+                anyhow::Error::from(err)
+            }
+        };
+
+        if is_rate_limited && retry_after.is_none() && remaining == Some(0) {
+            if let Some(reset) = reset {
+                count_down("Rate limit exceeded", until(reset)).await;
+                continue;
+            }
+        }
+
+        if !is_secondary_rate_limited && !is_server_error {
+            return Err(err);
+        }
+
+        retries += 1;
+        if retries > MAX_RETRIES {
+            return Err(err.context(format!("Request failed after {MAX_RETRIES} retries")));
+        }
+
+        let (reason, delay) = match retry_after {
+            Some(seconds) => (
+                "Secondary rate limit exceeded",
+                Duration::from_secs(seconds),
+            ),
+            None if is_secondary_rate_limited => (
+                "Secondary rate limit exceeded",
+                backoff(SECONDARY_RATE_LIMIT_BACKOFF, retries),
+            ),
+            None => ("Server error", backoff(SERVER_ERROR_BACKOFF, retries)),
+        };
+
+        count_down(reason, delay).await;
+    }
+}
+
+/// Waits for an exhausted rate limit to reset at the given Unix timestamp, while counting down the remaining seconds.
+pub(super) async fn wait_for_reset(reset: u64) {
+    count_down("Rate limit exhausted", until(reset)).await;
+}
+
+/// Returns the delay before the given (1-based) retry, doubling `initial` with each one.
+fn backoff(initial: Duration, retry: u32) -> Duration {
+    initial * 2_u32.saturating_pow(retry.saturating_sub(1))
+}
+
+/// Returns the duration until the given Unix timestamp (plus a second, to account for clock skew).
+fn until(timestamp: u64) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    Duration::from_secs(timestamp.saturating_sub(now) + 1)
+}
+
+/// Waits for the duration, while counting down the remaining seconds.
+async fn count_down(reason: &str, duration: Duration) {
+    let mut remaining = duration.as_secs();
+
+    while remaining > 0 {
+        eprint!("\r{reason}, resuming in {remaining}s ... ");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        remaining -= 1;
+    }
+
+    eprintln!("\r{reason}, resuming ...            ");
+}
@@ -0,0 +1,399 @@
+use chrono::{DateTime, Utc};
+use futures::{future, stream, StreamExt, TryStreamExt};
+use inquire::{Password, PasswordDisplayMode};
+use reqwest::{Client, RequestBuilder, Url};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::host::{
+    credentials::{send_request, GitCredential},
+    AuthOptions, GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream,
+    Token, TOKEN_USERNAME,
+};
+
+/// The environment variable to read a Gitlab access token from.
+const GITLAB_TOKEN_VAR: &str = "GITLAB_TOKEN";
+
+/// The response header containing the number of the next page, if any.
+const NEXT_PAGE_HEADER: &str = "x-next-page";
+
+const PER_PAGE: &str = "100";
+
+#[derive(Debug)]
+pub struct GitlabApi {
+    client: Client,
+    api_url: Url,
+    token: Option<SecretString>,
+    credential: Option<GitCredential>,
+}
+
+impl GitlabApi {
+    /// Creates a client for the Gitlab REST API at `api_url` (e.g. `https://gitlab.com/api/v4`).
+    pub fn new(api_url: Url, token: Option<SecretString>) -> Self {
+        Self {
+            client: Client::new(),
+            api_url,
+            token,
+            credential: None,
+        }
+    }
+
+    /// Creates a client for the Gitlab instance hosting the repository
+    /// (or at `api_url`, if provided),
+    /// authenticated via `GITLAB_TOKEN` (or the token file),
+    /// a git credential helper, or a token prompted for, as per `auth`.
+    pub fn authenticated(
+        repository: &GitRepositoryUrl,
+        api_url: Option<Url>,
+        auth: &AuthOptions,
+    ) -> anyhow::Result<Self> {
+        let api_url = match api_url {
+            Some(api_url) => api_url,
+            None => GitlabRepository::try_from(repository.clone())?.api_url()?,
+        };
+
+        let credential_url = repository.host_url()?;
+        let (token, credential) = Token::split(pick_token(&credential_url, auth)?);
+
+        Ok(Self {
+            credential,
+            ..Self::new(api_url, token)
+        })
+    }
+
+    fn get(&self, url: Url) -> RequestBuilder {
+        let request = self.client.get(url);
+
+        match &self.token {
+            Some(token) => request.header("PRIVATE-TOKEN", token.expose_secret()),
+            None => request,
+        }
+    }
+
+    fn project_url(&self, repository: &GitlabRepository) -> anyhow::Result<Url> {
+        let mut url = self.api_url.clone();
+
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Gitlab API url: {}", self.api_url))?
+            .pop_if_empty()
+            .extend(["projects", &repository.project_path()?]);
+
+        Ok(url)
+    }
+
+    async fn merge_requests_page(
+        &self,
+        project_url: &Url,
+        query: &PullRequestQuery,
+        page: u64,
+    ) -> anyhow::Result<(Vec<MergeRequest>, Option<u64>)> {
+        let mut url = project_url.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("Invalid Gitlab API url: {project_url}"))?
+            .push("merge_requests");
+
+        let mut request = self.get(url).query(&[
+            ("state", "merged"),
+            ("order_by", "updated_at"),
+            ("sort", "desc"),
+            ("per_page", PER_PAGE),
+            ("page", &page.to_string()),
+        ]);
+
+        if let Some(base_ref) = &query.base_ref {
+            request = request.query(&[("target_branch", base_ref)]);
+        }
+
+        // A merge request can't have been updated any earlier than it got merged:
+        if let Some(merged_after) = query.merged_after {
+            request = request.query(&[("updated_after", merged_after.to_rfc3339())]);
+        }
+
+        let response = send_request(request, self.credential.as_ref()).await?;
+
+        let next_page = response
+            .headers()
+            .get(NEXT_PAGE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+
+        let merge_requests = response.json().await?;
+
+        Ok((merge_requests, next_page))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GitlabRepository(GitRepositoryUrl);
+
+impl TryFrom<GitRepositoryUrl> for GitlabRepository {
+    type Error = anyhow::Error;
+
+    fn try_from(repository: GitRepositoryUrl) -> Result<Self, anyhow::Error> {
+        let url = &repository.url_string;
+
+        if repository.parsed_url.host().is_none() {
+            anyhow::bail!("No host found in url: {url}");
+        }
+
+        if repository.coordinates()?.namespace.is_empty() {
+            anyhow::bail!("Not a Gitlab project url: {url}");
+        }
+
+        Ok(Self(repository))
+    }
+}
+
+impl GitlabRepository {
+    /// Returns the project's full path, including any (sub)groups.
+    pub fn project_path(&self) -> anyhow::Result<String> {
+        let coordinates = self.0.coordinates()?;
+
+        let mut segments = coordinates.namespace;
+        segments.push(coordinates.name);
+
+        Ok(segments.join("/"))
+    }
+
+    /// Returns the url of the REST API of the Gitlab instance hosting the project.
+    pub fn api_url(&self) -> anyhow::Result<Url> {
+        let url = &self.0.parsed_url;
+
+        let host = url
+            .host()
+            .ok_or_else(|| anyhow::anyhow!("No host found in url: {url}"))?;
+
+        let (scheme, port) = match url.scheme() {
+            Some(scheme @ ("http" | "https")) => (scheme, url.port()),
+            _ => ("https", None),
+        };
+
+        let port = port.map(|port| format!(":{port}")).unwrap_or_default();
+
+        Ok(Url::parse(&format!("{scheme}://{host}{port}/api/v4"))?)
+    }
+}
+
+impl GitHost for GitlabApi {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        let project_url = GitlabRepository::try_from(repository.clone())
+            .and_then(|safe_repository| self.project_url(&safe_repository));
+
+        let project_url = match project_url {
+            Ok(project_url) => project_url,
+            Err(err) => return stream::once(future::ready(Err(err))).boxed(),
+        };
+
+        let pages = stream::try_unfold(Some(1), move |page| {
+            let project_url = project_url.clone();
+            async move {
+                let Some(page) = page else {
+                    return Ok::<_, anyhow::Error>(None);
+                };
+
+                let (merge_requests, next_page) =
+                    self.merge_requests_page(&project_url, query, page).await?;
+
+                Ok(Some((merge_requests, next_page)))
+            }
+        });
+
+        pages
+            .map_ok(|merge_requests| stream::iter(merge_requests).map(Ok))
+            .try_flatten()
+            .try_filter(move |merge_request| {
+                let is_merged = merge_request
+                    .merged_at
+                    .is_some_and(|merged_at| query.contains_merged_at(merged_at));
+
+                future::ready(is_merged)
+            })
+            .and_then(|merge_request| future::ready(git_pull_request(merge_request)))
+            .boxed()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct MergeRequest {
+    iid: u64,
+    title: Option<String>,
+    author: Option<Author>,
+    #[serde(default)]
+    labels: Vec<String>,
+    target_branch: String,
+    sha: Option<String>,
+    merge_commit_sha: Option<String>,
+    squash_commit_sha: Option<String>,
+    diff_refs: Option<DiffRefs>,
+    merged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Author {
+    username: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct DiffRefs {
+    base_sha: String,
+}
+
+/// Maps a merge request to a pull request.
+///
+/// Merge request listings don't include the `diff_refs`, in which case the base commit
+/// gets derived from the merge commit locally, instead of requesting each merge request individually.
+fn git_pull_request(merge_request: MergeRequest) -> anyhow::Result<GitPullRequest> {
+    let Some(merge_sha) = merge_request
+        .merge_commit_sha
+        .or(merge_request.squash_commit_sha)
+        .or(merge_request.sha)
+    else {
+        anyhow::bail!("Could not find merge commit sha");
+    };
+
+    Ok(GitPullRequest {
+        identifier: merge_request.iid.to_string(),
+        title: merge_request.title,
+        author: merge_request.author.map(|author| author.username),
+        labels: merge_request.labels,
+        base_ref: merge_request.target_branch,
+        base_sha: merge_request.diff_refs.map(|diff_refs| diff_refs.base_sha),
+        merge_sha,
+        merged_at: merge_request.merged_at,
+    })
+}
+
+fn pick_token(url: &Url, auth: &AuthOptions) -> anyhow::Result<Option<Token>> {
+    auth.token_or_prompt(&[GITLAB_TOKEN_VAR], url, TOKEN_USERNAME, || {
+        let token = Password::new("Personal access token (leave empty for no authentication):")
+            .with_display_toggle_enabled()
+            .with_display_mode(PasswordDisplayMode::Hidden)
+            .prompt()?;
+
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SecretString::from(token)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use crate::test_support::{TestResponse, TestServer};
+
+    use super::*;
+
+    fn merge_request(iid: u64, merged_at: &str) -> serde_json::Value {
+        json!({
+            "iid": iid,
+            "title": format!("Merge request {iid}"),
+            "author": { "username": "octocat" },
+            "labels": ["bug"],
+            "target_branch": "main",
+            "sha": "c".repeat(40),
+            "merge_commit_sha": format!("{iid:040}"),
+            "squash_commit_sha": null,
+            "merged_at": merged_at,
+        })
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_requests_one_page_at_a_time() {
+        let server = TestServer::start(|request| match request.query("page").as_deref() {
+            Some("1") => TestResponse::json(
+                200,
+                json!([
+                    merge_request(3, "2026-10-03T00:00:00Z"),
+                    merge_request(2, "2026-10-02T00:00:00Z"),
+                ]),
+            )
+            .header(NEXT_PAGE_HEADER, "2"),
+            Some("2") => TestResponse::json(
+                200,
+                json!([
+                    merge_request(1, "2026-10-01T00:00:00Z"),
+                    merge_request(0, "2026-09-01T00:00:00Z"),
+                ]),
+            )
+            .header(NEXT_PAGE_HEADER, ""),
+            _ => TestResponse::json(404, json!({ "message": "404 Not Found" })),
+        });
+
+        let api = GitlabApi::new(
+            server.url().join("api/v4").unwrap(),
+            Some(SecretString::from("glpat-secret")),
+        );
+
+        let repository =
+            GitRepositoryUrl::from_str("https://gitlab.example.com/group/subgroup/project.git")
+                .unwrap();
+
+        let query = PullRequestQuery {
+            base_ref: Some("main".to_owned()),
+            merged_after: Some("2026-09-30T00:00:00Z".parse().unwrap()),
+            merged_before: Some("2026-10-31T00:00:00Z".parse().unwrap()),
+        };
+
+        let pull_requests: Vec<GitPullRequest> = api
+            .merged_pull_requests(&repository, &query)
+            .try_collect()
+            .await
+            .unwrap();
+
+        let identifiers: Vec<&str> = pull_requests
+            .iter()
+            .map(|pull_request| pull_request.identifier.as_str())
+            .collect();
+        assert_eq!(identifiers, ["3", "2", "1"]);
+
+        let pull_request = &pull_requests[0];
+        assert_eq!(pull_request.merge_sha, format!("{:040}", 3));
+        assert_eq!(pull_request.base_sha, None);
+        assert_eq!(pull_request.base_ref, "main");
+        assert_eq!(pull_request.author.as_deref(), Some("octocat"));
+        assert_eq!(pull_request.labels, ["bug"]);
+
+        // Only the pages get requested, without any requests for individual merge requests:
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+
+        for request in &requests {
+            assert_eq!(request.method, "GET");
+            assert!(request
+                .path
+                .starts_with("/api/v4/projects/group%2Fsubgroup%2Fproject/merge_requests?"));
+            assert_eq!(request.header("PRIVATE-TOKEN"), Some("glpat-secret"));
+            assert_eq!(request.query("state").as_deref(), Some("merged"));
+            assert_eq!(request.query("target_branch").as_deref(), Some("main"));
+            assert!(request.query("updated_after").is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_fails_on_error_status() {
+        let server = TestServer::start(|_request| {
+            TestResponse::json(401, json!({ "message": "401 Unauthorized" }))
+        });
+
+        let api = GitlabApi::new(server.url().join("api/v4").unwrap(), None);
+
+        let repository =
+            GitRepositoryUrl::from_str("https://gitlab.example.com/group/project.git").unwrap();
+
+        let result: anyhow::Result<Vec<GitPullRequest>> = api
+            .merged_pull_requests(&repository, &PullRequestQuery::default())
+            .try_collect()
+            .await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,289 @@
+use std::path::PathBuf;
+
+use futures::{stream, StreamExt, TryStreamExt};
+use git2::Repository as GitRepository;
+use regex::Regex;
+
+use crate::host::{GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream};
+
+use super::refs::{committed_at, resolve_base, FirstParentHistory};
+
+/// The patterns recognizing the merge commit messages of common hosts.
+pub const DEFAULT_MERGE_MESSAGE_PATTERNS: &[&str] = &[
+    // Github: "Merge pull request #123 from owner/branch", followed by the title.
+    r"\AMerge pull request #(?<number>\d+) from \S+(?:\n\n(?<title>[^\n]+))?",
+    // Github (squashed): "Title (#123)".
+    r"\A(?<title>[^\n]+) \(#(?<number>\d+)\)(?:\n|\z)",
+    // Gitlab: "Merge branch 'branch' into 'main'", followed by the title and "See merge request group/project!123".
+    r"\AMerge branch '[^']+' into '[^']+'\n\n(?<title>[^\n]+)(?s:.*)See merge request \S*!(?<number>\d+)",
+    // Bitbucket: "Merged in branch (pull request #123)", followed by the title.
+    r"\AMerged in \S+ \(pull request #(?<number>\d+)\)(?:\n\n(?<title>[^\n]+))?",
+    // Azure DevOps: "Merged PR 123: Title".
+    r"\AMerged PR (?<number>\d+): (?<title>[^\n]+)",
+];
+
+/// A host reconstructing merged pull requests from the messages of the commits
+/// on the base branch's first-parent history, for repositories without any host to request them from
+/// (e.g. mirrors).
+///
+/// Commits are recognized by regular expressions, each of which captures the pull request's number
+/// via a `number` group and (optionally) its title via a `title` group.
+pub struct MergeMessagesHost {
+    directory: PathBuf,
+    patterns: Vec<Regex>,
+}
+
+impl MergeMessagesHost {
+    /// Creates a host for the repository within `directory`, recognizing merge commits
+    /// by the given patterns, or by `DEFAULT_MERGE_MESSAGE_PATTERNS`, if there are none.
+    pub fn new(directory: PathBuf, patterns: &[String]) -> anyhow::Result<Self> {
+        let patterns: Vec<&str> = if patterns.is_empty() {
+            DEFAULT_MERGE_MESSAGE_PATTERNS.to_vec()
+        } else {
+            patterns.iter().map(String::as_str).collect()
+        };
+
+        let patterns: Vec<Regex> = patterns
+            .into_iter()
+            .map(Regex::new)
+            .collect::<Result<_, _>>()?;
+
+        for pattern in &patterns {
+            if !pattern.capture_names().any(|name| name == Some("number")) {
+                anyhow::bail!("Merge message pattern {pattern} lacks a `(?<number>…)` group");
+            }
+        }
+
+        Ok(Self {
+            directory,
+            patterns,
+        })
+    }
+
+    fn pull_requests(
+        &self,
+        repository: &GitRepositoryUrl,
+        query: &PullRequestQuery,
+    ) -> anyhow::Result<Vec<GitPullRequest>> {
+        let repository_handle = GitRepository::open(&self.directory)?;
+
+        let (base_ref, base) = resolve_base(&repository_handle, repository, query)?;
+
+        let history = FirstParentHistory::walk(&repository_handle, base, query)?;
+
+        let mut pull_requests: Vec<GitPullRequest> = vec![];
+
+        for &commit_id in history.commit_ids().iter().rev() {
+            let commit = repository_handle.find_commit(commit_id)?;
+
+            let merged_at = committed_at(&commit);
+
+            if !query.contains_merged_at(merged_at) {
+                continue;
+            }
+
+            let (Some(message), Ok(base_sha)) = (commit.message(), commit.parent_id(0)) else {
+                continue;
+            };
+
+            let Some(captures) = self
+                .patterns
+                .iter()
+                .find_map(|pattern| pattern.captures(message))
+            else {
+                continue;
+            };
+
+            let Some(number) = captures.name("number") else {
+                continue;
+            };
+
+            let title = captures
+                .name("title")
+                .map(|title| title.as_str())
+                .or(commit.summary())
+                .map(str::to_owned);
+
+            pull_requests.push(GitPullRequest {
+                identifier: number.as_str().to_owned(),
+                title,
+                author: None,
+                labels: vec![],
+                base_ref: base_ref.clone(),
+                base_sha: Some(base_sha.to_string()),
+                merge_sha: commit_id.to_string(),
+                merged_at: Some(merged_at),
+            });
+        }
+
+        Ok(pull_requests)
+    }
+}
+
+impl GitHost for MergeMessagesHost {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        stream::once(async move { self.pull_requests(repository, query) })
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use git2::{Oid, Signature};
+
+    use crate::test_support::TestDirectory;
+
+    use super::*;
+
+    fn commit(repository: &GitRepository, message: &str) -> Oid {
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let tree = repository
+            .find_tree(repository.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parent = repository
+            .head()
+            .ok()
+            .map(|head| head.peel_to_commit().unwrap());
+        let parents: Vec<_> = parent.iter().collect();
+
+        repository
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )
+            .unwrap()
+    }
+
+    async fn merged_pull_requests(
+        directory: &TestDirectory,
+        patterns: &[String],
+    ) -> Vec<GitPullRequest> {
+        let host = MergeMessagesHost::new(directory.path().to_owned(), patterns).unwrap();
+        let repository = GitRepositoryUrl::from_str("https://github.com/owner/name.git").unwrap();
+        let query = PullRequestQuery::default();
+
+        host.merged_pull_requests(&repository, &query)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    fn summaries(pull_requests: &[GitPullRequest]) -> Vec<(&str, Option<&str>)> {
+        pull_requests
+            .iter()
+            .map(|pull_request| {
+                (
+                    pull_request.identifier.as_str(),
+                    pull_request.title.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_recognizes_default_merge_messages() {
+        let directory = TestDirectory::new();
+        let repository = GitRepository::init(directory.path()).unwrap();
+
+        let initial = commit(&repository, "Initial commit");
+        let github = commit(
+            &repository,
+            "Merge pull request #1 from owner/feature\n\nAdd feature",
+        );
+        commit(&repository, "Fix crash (#2)\n\n* Check for null");
+        commit(&repository, "Merge pull request #3 from owner/docs");
+        commit(
+            &repository,
+            "Merge branch 'typo' into 'main'\n\nFix typo\n\nCloses #9\n\nSee merge request group/project!4",
+        );
+        commit(
+            &repository,
+            "Merged in topic (pull request #5)\n\nImprove docs\n\nApproved-by: Someone",
+        );
+        let azure = commit(&repository, "Merged PR 6: Update dependencies");
+
+        let pull_requests = merged_pull_requests(&directory, &[]).await;
+
+        assert_eq!(
+            summaries(&pull_requests),
+            [
+                ("6", Some("Update dependencies")),
+                ("5", Some("Improve docs")),
+                ("4", Some("Fix typo")),
+                ("3", Some("Merge pull request #3 from owner/docs")),
+                ("2", Some("Fix crash")),
+                ("1", Some("Add feature")),
+            ]
+        );
+
+        assert_eq!(pull_requests[0].merge_sha, azure.to_string());
+        assert_eq!(pull_requests[5].merge_sha, github.to_string());
+        assert_eq!(pull_requests[5].base_sha, Some(initial.to_string()));
+        let branch = repository.head().unwrap().shorthand().unwrap().to_owned();
+        assert!(pull_requests
+            .iter()
+            .all(|pull_request| pull_request.base_ref == branch));
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_rejects_titles_merely_mentioning_numbers() {
+        let directory = TestDirectory::new();
+        let repository = GitRepository::init(directory.path()).unwrap();
+
+        commit(&repository, "Initial commit");
+        commit(&repository, "Fix regression from #12");
+        commit(
+            &repository,
+            "Revert \"Add feature (#13)\"\n\nThis reverts commit abc.",
+        );
+        commit(&repository, "Fix #14 (#15) again");
+        commit(&repository, "#16");
+
+        let pull_requests = merged_pull_requests(&directory, &[]).await;
+
+        assert_eq!(summaries(&pull_requests), []);
+    }
+
+    #[tokio::test]
+    async fn merged_pull_requests_recognizes_custom_patterns() {
+        let directory = TestDirectory::new();
+        let repository = GitRepository::init(directory.path()).unwrap();
+
+        commit(&repository, "Initial commit");
+        commit(&repository, "Merge pull request #1 from owner/feature");
+        commit(&repository, "[PR-2] Add feature");
+        commit(&repository, "Land !3\n\nSome details");
+
+        let patterns = [
+            r"\A\[PR-(?<number>\d+)\] (?<title>[^\n]+)".to_owned(),
+            r"\ALand !(?<number>\d+)".to_owned(),
+        ];
+
+        let pull_requests = merged_pull_requests(&directory, &patterns).await;
+
+        assert_eq!(
+            summaries(&pull_requests),
+            [("3", Some("Land !3")), ("2", Some("Add feature"))]
+        );
+    }
+
+    #[test]
+    fn new_rejects_patterns_without_number_group() {
+        let directory = TestDirectory::new();
+        let patterns = [r"\AMerged (?<title>[^\n]+)".to_owned()];
+
+        assert!(MergeMessagesHost::new(directory.path().to_owned(), &patterns).is_err());
+    }
+}
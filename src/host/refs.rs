@@ -0,0 +1,238 @@
+use std::{cmp::Reverse, path::PathBuf};
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{stream, StreamExt, TryStreamExt};
+use git2::{Commit as GitCommit, Oid, Repository as GitRepository};
+
+use crate::host::{GitHost, GitPullRequest, GitRepositoryUrl, PullRequestQuery, PullRequestStream};
+
+/// The glob of the refs of pull request heads, as fetched via
+/// `git fetch origin '+refs/pull/*/head:refs/pull/*/head'`.
+const PULL_REQUEST_HEADS_GLOB: &str = "refs/pull/*/head";
+
+/// A host deriving merged pull requests from the repository's local `refs/pull/*/head` refs,
+/// which Github exposes for every pull request, without requiring any network access.
+///
+/// A pull request counts as merged by the first commit on the base branch's first-parent history
+/// that its head is reachable from. Since squashed or rebased pull requests don't keep their heads,
+/// only those merged via merge commits (or fast-forwards) are found, which are the only ones
+/// with internal commits to skip, however.
+pub struct PullRequestRefsHost {
+    directory: PathBuf,
+}
+
+impl PullRequestRefsHost {
+    /// Creates a host for the repository within `directory`.
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    fn pull_requests(
+        &self,
+        repository: &GitRepositoryUrl,
+        query: &PullRequestQuery,
+    ) -> anyhow::Result<Vec<GitPullRequest>> {
+        let repository_handle = GitRepository::open(&self.directory)?;
+
+        let (base_ref, base) = resolve_base(&repository_handle, repository, query)?;
+
+        let history = FirstParentHistory::walk(&repository_handle, base, query)?;
+
+        let mut pull_requests: Vec<GitPullRequest> = vec![];
+
+        for reference in repository_handle.references_glob(PULL_REQUEST_HEADS_GLOB)? {
+            let reference = reference?;
+
+            let Some(identifier) = reference
+                .name()
+                .and_then(|name| name.strip_prefix("refs/pull/"))
+                .and_then(|name| name.strip_suffix("/head"))
+            else {
+                continue;
+            };
+
+            let Ok(head) = reference.peel_to_commit() else {
+                continue;
+            };
+
+            // A pull request can't have been merged any earlier than its head got committed:
+            if query
+                .merged_before
+                .is_some_and(|merged_before| committed_at(&head) > merged_before)
+            {
+                continue;
+            }
+
+            let Some(merge_commit) = history.merging(&repository_handle, head.id())? else {
+                continue;
+            };
+
+            let merged_at = committed_at(&merge_commit);
+
+            if !query.contains_merged_at(merged_at) {
+                continue;
+            }
+
+            let Ok(base_sha) = merge_commit.parent_id(0) else {
+                continue;
+            };
+
+            pull_requests.push(GitPullRequest {
+                identifier: identifier.to_owned(),
+                title: None,
+                author: None,
+                labels: vec![],
+                base_ref: base_ref.clone(),
+                base_sha: Some(base_sha.to_string()),
+                merge_sha: merge_commit.id().to_string(),
+                merged_at: Some(merged_at),
+            });
+        }
+
+        pull_requests.sort_by_key(|pull_request| Reverse(pull_request.merged_at));
+
+        Ok(pull_requests)
+    }
+}
+
+impl GitHost for PullRequestRefsHost {
+    fn merged_pull_requests<'a>(
+        &'a self,
+        repository: &'a GitRepositoryUrl,
+        query: &'a PullRequestQuery,
+    ) -> PullRequestStream<'a> {
+        stream::once(async move { self.pull_requests(repository, query) })
+            .map_ok(|pull_requests| stream::iter(pull_requests).map(Ok))
+            .try_flatten()
+            .boxed()
+    }
+}
+
+/// Resolves the query's base branch (or `HEAD`, if there is none) to its tip,
+/// preferring the remote-tracking branch of the repository's remote over a local branch.
+pub(super) fn resolve_base(
+    repository_handle: &GitRepository,
+    repository: &GitRepositoryUrl,
+    query: &PullRequestQuery,
+) -> anyhow::Result<(String, Oid)> {
+    let Some(base_ref) = &query.base_ref else {
+        let head = repository_handle.head()?;
+        let name = head.shorthand().unwrap_or("HEAD").to_owned();
+        return Ok((name, head.peel_to_commit()?.id()));
+    };
+
+    let mut candidates: Vec<String> = vec![];
+
+    for remote in repository_handle.remotes()?.iter().flatten() {
+        let remote_url = repository_handle
+            .find_remote(remote)?
+            .url()
+            .map(str::to_owned);
+
+        if remote_url.as_deref() == Some(repository.url_string.as_str()) {
+            candidates.push(format!("refs/remotes/{remote}/{base_ref}"));
+        }
+    }
+
+    candidates.push(format!("refs/heads/{base_ref}"));
+
+    for candidate in &candidates {
+        if let Ok(reference) = repository_handle.find_reference(candidate) {
+            return Ok((base_ref.clone(), reference.peel_to_commit()?.id()));
+        }
+    }
+
+    anyhow::bail!("Could not find base branch {base_ref}")
+}
+
+/// The first-parent history of a base branch within a query's time window.
+pub(super) struct FirstParentHistory {
+    /// The commits, ordered from least to most recently committed.
+    commits: Vec<Oid>,
+
+    /// The latest commit preceding the time window, if any.
+    preceding: Option<Oid>,
+}
+
+impl FirstParentHistory {
+    pub(super) fn walk(
+        repository: &GitRepository,
+        tip: Oid,
+        query: &PullRequestQuery,
+    ) -> anyhow::Result<Self> {
+        let mut commits: Vec<Oid> = vec![];
+        let mut preceding: Option<Oid> = None;
+
+        let mut commit = Some(repository.find_commit(tip)?);
+
+        while let Some(current) = commit {
+            let is_preceding = query
+                .merged_after
+                .is_some_and(|merged_after| committed_at(&current) < merged_after);
+
+            if is_preceding {
+                preceding = Some(current.id());
+                break;
+            }
+
+            commits.push(current.id());
+            commit = current.parent(0).ok();
+        }
+
+        commits.reverse();
+
+        Ok(Self { commits, preceding })
+    }
+
+    /// Returns the commits, ordered from least to most recently committed.
+    pub(super) fn commit_ids(&self) -> &[Oid] {
+        &self.commits
+    }
+
+    /// Returns the first commit of the history that the given commit is reachable from,
+    /// unless it had already been reachable before the time window.
+    fn merging<'r>(
+        &self,
+        repository: &'r GitRepository,
+        commit_id: Oid,
+    ) -> anyhow::Result<Option<GitCommit<'r>>> {
+        let contains = |commit: Oid| -> anyhow::Result<bool> {
+            Ok(commit == commit_id || repository.graph_descendant_of(commit, commit_id)?)
+        };
+
+        let Some(&latest) = self.commits.last() else {
+            return Ok(None);
+        };
+
+        if !contains(latest)? {
+            return Ok(None);
+        }
+
+        if let Some(preceding) = self.preceding {
+            if contains(preceding)? {
+                return Ok(None);
+            }
+        }
+
+        // Once reachable, a commit stays reachable from all later commits of the history:
+        let (mut lower, mut upper) = (0, self.commits.len() - 1);
+
+        while lower < upper {
+            let middle = lower + (upper - lower) / 2;
+
+            if contains(self.commits[middle])? {
+                upper = middle;
+            } else {
+                lower = middle + 1;
+            }
+        }
+
+        Ok(Some(repository.find_commit(self.commits[lower])?))
+    }
+}
+
+pub(super) fn committed_at(commit: &GitCommit<'_>) -> DateTime<Utc> {
+    Utc.timestamp_opt(commit.committer().when().seconds(), 0)
+        .single()
+        .unwrap_or_default()
+}
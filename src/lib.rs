@@ -1,3 +1,6 @@
 pub mod command;
 pub mod git;
 pub mod host;
+
+#[cfg(test)]
+mod test_support;
@@ -1,6 +1,12 @@
-use args::bisect::Command::SkipPullRequests;
+use args::{
+    bisect::Command::{ByPullRequest, Run, SkipPullRequests, Start},
+    pull_requests::Command::Export,
+};
 use clap::Parser;
 
+#[cfg(feature = "github")]
+use args::auth::Command::Login;
+
 use self::args::*;
 
 mod args;
@@ -25,7 +31,13 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let result = match args_command {
+        #[cfg(feature = "github")]
+        Command::Auth(Login(command)) => command.run().await,
         Command::Bisect(SkipPullRequests(command)) => command.run().await,
+        Command::Bisect(Start(command)) => command.run().await,
+        Command::Bisect(ByPullRequest(command)) => command.run().await,
+        Command::Bisect(Run(command)) => command.run().await,
+        Command::PullRequests(Export(command)) => command.run().await,
     };
 
     if let Ok(exit_status) = &result {
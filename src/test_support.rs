@@ -0,0 +1,216 @@
+//! Helpers for tests, standing in for hosts' APIs and providing temporary directories.
+
+// Not every helper is used with every combination of features:
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use url::Url;
+
+/// A request received by a `TestServer`.
+#[derive(Clone, Debug)]
+pub(crate) struct TestRequest {
+    pub(crate) method: String,
+
+    /// The request's path, including its query (e.g. `/api/v4/projects?page=1`).
+    pub(crate) path: String,
+
+    pub(crate) headers: Vec<(String, String)>,
+
+    pub(crate) body: String,
+}
+
+impl TestRequest {
+    /// Returns the value of the header with the given (case-insensitive) name, if any.
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the value of the query parameter with the given name, if any.
+    pub(crate) fn query(&self, name: &str) -> Option<String> {
+        let url = Url::parse(&format!("http://localhost{path}", path = self.path)).ok()?;
+
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+}
+
+/// A response to be sent by a `TestServer`.
+#[derive(Clone, Debug)]
+pub(crate) struct TestResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl TestResponse {
+    pub(crate) fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type".to_owned(), "application/json".to_owned())],
+            body: body.to_string(),
+        }
+    }
+
+    pub(crate) fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+/// A minimal HTTP/1.1 server on a random local port, answering each request via a handler
+/// (one connection at a time), while recording the requests it received.
+pub(crate) struct TestServer {
+    url: Url,
+    requests: Arc<Mutex<Vec<TestRequest>>>,
+}
+
+impl TestServer {
+    pub(crate) fn start(handler: impl Fn(&TestRequest) -> TestResponse + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Could not bind test server");
+        let port = listener.local_addr().expect("No local address").port();
+
+        let requests: Arc<Mutex<Vec<TestRequest>>> = Arc::default();
+
+        thread::spawn({
+            let requests = requests.clone();
+            move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else {
+                        continue;
+                    };
+
+                    let Some(request) = read_request(&stream) else {
+                        continue;
+                    };
+
+                    let response = handler(&request);
+                    requests.lock().unwrap().push(request);
+
+                    let _ = write_response(&mut stream, &response);
+                }
+            }
+        });
+
+        Self {
+            url: Url::parse(&format!("http://127.0.0.1:{port}/")).unwrap(),
+            requests,
+        }
+    }
+
+    /// Returns the server's base url (i.e. `http://127.0.0.1:<port>/`).
+    pub(crate) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the requests received so far, in order.
+    pub(crate) fn requests(&self) -> Vec<TestRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Option<TestRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_owned();
+    let path = parts.next()?.to_owned();
+
+    let mut headers = vec![];
+
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        let (name, value) = line.split_once(':')?;
+        headers.push((name.trim().to_owned(), value.trim().to_owned()));
+    }
+
+    let content_length: usize = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or_default();
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(TestRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn write_response(stream: &mut TcpStream, response: &TestResponse) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {status} Test\r\ncontent-length: {length}\r\nconnection: close\r\n",
+        status = response.status,
+        length = response.body.len()
+    );
+
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+/// A temporary directory, which gets removed (along with its contents) once dropped.
+pub(crate) struct TestDirectory {
+    path: PathBuf,
+}
+
+impl TestDirectory {
+    pub(crate) fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "git-assist-test-{process}-{count}",
+            process = std::process::id(),
+            count = COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("Could not create test directory");
+
+        Self { path }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}